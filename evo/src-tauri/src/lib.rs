@@ -1,14 +1,125 @@
 mod server;
-use server::{MockApi, AppState, ServerConfig, RequestLog};
+use server::{MockApi, AppState, ServerConfig, RequestLog, LogFilter, WeightedResponse, Environment, ConfigLoadError, MockFilter, ExpectedRequest, ToggleRef, RequestFixture, Maintenance};
 use std::sync::{Arc, Mutex};
 use std::collections::{HashMap, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use tauri::{State, Manager, AppHandle};
 
 const DATA_FILE_NAME: &str = "mocks.json";
 const DB_CONFIG_FILE_NAME: &str = "db_connections.json";
 const SERVER_CONFIG_FILE_NAME: &str = "server_config.json";
+const SEED_DIR_NAME: &str = "mocks.d";
+const LOG_DB_FILE_NAME: &str = "request_logs.db";
+const ENVIRONMENTS_FILE_NAME: &str = "environments.json";
+const FIXTURES_FILE_NAME: &str = "fixtures.json";
+
+// Parse a JSON file from the app data directory; None if it's missing or unreadable.
+// A file that exists but fails to parse is copied to `<name>.bak-<timestamp>` and reported
+// through a `config-load-error` event. The `<name>.bak` kept by save_json_file is loaded
+// instead when it parses. Either way the error is recorded in AppState; without a backup,
+// saves leave the original alone until resolve_config_load_error is called.
+fn load_json_file<T: serde::de::DeserializeOwned>(app_handle: &AppHandle, path: &Path) -> Option<T> {
+    load_json_file_or_backup(app_handle, path).map(|(value, _)| value)
+}
+
+// load_json_file, also returning the reported load error when the data came from the backup
+fn load_json_file_or_backup<T: serde::de::DeserializeOwned>(app_handle: &AppHandle, path: &Path) -> Option<(T, Option<ConfigLoadError>)> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Some((value, None)),
+        Err(e) => {
+            let backup_path = backup_path(path);
+            let backup = fs::read_to_string(&backup_path).ok().and_then(|content| serde_json::from_str(&content).ok());
+            let recovered_from = backup.is_some().then(|| backup_path.display().to_string());
+            let load_error = report_load_error(app_handle, path, e.to_string(), recovered_from);
+            backup.map(|value| (value, Some(load_error)))
+        }
+    }
+}
+
+// The previous version of a JSON file, kept by save_json_file
+fn backup_path(path: &Path) -> PathBuf {
+    let file = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}.bak", file))
+}
+
+fn report_load_error(app_handle: &AppHandle, path: &Path, error: String, recovered_from: Option<String>) -> ConfigLoadError {
+    let file = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    println!("Failed to parse {}: {}", file, error);
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup = path.with_file_name(format!("{}.bak-{}", file, timestamp));
+    let backup = fs::copy(path, &backup).ok().map(|_| backup.display().to_string());
+
+    let load_error = ConfigLoadError { file: file.clone(), error, backup, recovered_from };
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        if let Ok(mut errors) = state.config_load_errors.lock() {
+            errors.insert(file, load_error.clone());
+        }
+    }
+    let _ = app_handle.emit("config-load-error", load_error.clone());
+    load_error
+}
+
+fn save_json_file<T: serde::Serialize + ?Sized>(app_handle: &AppHandle, path: &Path, value: &T) -> Result<(), String> {
+    commit_json_file(stage_json_file(app_handle, path, value)?)
+}
+
+// A new version of a JSON file, written next to it and waiting for commit_json_file
+struct StagedFile {
+    path: PathBuf,
+    tmp: PathBuf,
+}
+
+// Numbers the temp files, so concurrent saves of one file never write to the same temp path
+static TMP_FILE_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn stage_json_file<T: serde::Serialize + ?Sized>(app_handle: &AppHandle, path: &Path, value: &T) -> Result<StagedFile, String> {
+    let file = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        let errors = state.config_load_errors.lock().map_err(|e| e.to_string())?;
+        // Saving over the file is fine once its data came back from the backup
+        if errors.get(&file).is_some_and(|e| e.recovered_from.is_none()) {
+            return Err(format!("{} failed to load and won't be overwritten until the load error is resolved", file));
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+
+    // Write a temp file and rename it over the original, so a crash mid-write can't leave
+    // a truncated file behind. The previous version is kept as `<name>.bak` for load_json_file,
+    // unless it's unparseable itself and would replace a good backup.
+    let n = TMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let tmp = path.with_file_name(format!("{}.{}.{}.tmp", file, std::process::id(), n));
+    {
+        use std::io::Write;
+        let mut tmp_file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+        tmp_file.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+        tmp_file.sync_all().map_err(|e| e.to_string())?;
+    }
+    Ok(StagedFile { path: path.to_path_buf(), tmp })
+}
+
+fn commit_json_file(staged: StagedFile) -> Result<(), String> {
+    let StagedFile { path, tmp } = staged;
+    let file = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+    if let Ok(previous) = fs::read(&path) {
+        if serde_json::from_slice::<serde_json::Value>(&previous).is_ok() {
+            if let Err(e) = fs::write(backup_path(&path), previous) {
+                println!("Failed to back up {}: {}", file, e);
+            }
+        }
+    }
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(())
+}
 
 fn get_data_path(app_handle: &AppHandle) -> Option<PathBuf> {
     app_handle.path().app_data_dir().ok().map(|p| p.join(DATA_FILE_NAME))
@@ -16,26 +127,105 @@ fn get_data_path(app_handle: &AppHandle) -> Option<PathBuf> {
 
 fn save_mocks(app_handle: &AppHandle, mocks: &HashMap<String, MockApi>) -> Result<(), String> {
     if let Some(path) = get_data_path(app_handle) {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-        let json = serde_json::to_string_pretty(mocks).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
+        save_json_file(app_handle, &path, mocks)?;
     }
     Ok(())
 }
 
+// Falls back to mocks.json.bak when mocks.json won't parse, emitting `mocks-load-fallback`
 fn load_mocks(app_handle: &AppHandle) -> HashMap<String, MockApi> {
-    if let Some(path) = get_data_path(app_handle) {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(mocks) = serde_json::from_str(&content) {
-                    return mocks;
-                }
+    let Some(path) = get_data_path(app_handle) else {
+        return HashMap::new();
+    };
+    match load_json_file_or_backup(app_handle, &path) {
+        Some((mocks, Some(load_error))) => {
+            println!("Loaded mocks from {} instead", load_error.recovered_from.as_deref().unwrap_or_default());
+            let _ = app_handle.emit("mocks-load-fallback", load_error);
+            mocks
+        }
+        Some((mocks, None)) => mocks,
+        None => HashMap::new(),
+    }
+}
+
+fn get_seed_dir(app_handle: &AppHandle, config: &ServerConfig) -> Option<PathBuf> {
+    match config.mocks_dir.as_deref() {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => app_handle.path().app_data_dir().ok().map(|p| p.join(SEED_DIR_NAME)),
+    }
+}
+
+// Seed files are named after the route they serve: `GET__api__users.json` becomes
+// `GET /api/users`. A first line of the form `// POST /users/:id` overrides the file
+// name (useful for paths that can't be expressed as file names) and is stripped from the body.
+fn parse_seed_file(path: &Path) -> Result<MockApi, String> {
+    let file_name = path.file_name().and_then(|f| f.to_str()).ok_or("file name isn't valid UTF-8")?;
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_seed(file_name, content)
+}
+
+// Methods a seed file may declare, in its name or its header line
+const SEED_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+fn parse_seed(file_name: &str, content: String) -> Result<MockApi, String> {
+    let (stem, response_type) = match file_name.rsplit_once('.') {
+        Some((stem, "json")) => (stem, "json"),
+        Some((stem, "js")) => (stem, "js"),
+        _ => return Err("not a .json or .js file".to_string()),
+    };
+    let known_method = |method: &str| SEED_METHODS.contains(&method.to_uppercase().as_str());
+
+    let header = content
+        .lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix("//"))
+        .and_then(|rest| rest.trim().split_once(' '))
+        .filter(|(method, path)| known_method(method) && path.trim().starts_with('/'))
+        .map(|(method, path)| (method.to_string(), path.trim().to_string()));
+
+    let (method, route, body) = match header {
+        Some((method, route)) => {
+            let body = content.split_once('\n').map(|(_, b)| b).unwrap_or("").to_string();
+            (method, route, body)
+        }
+        None => {
+            // Without the separator the whole stem would become the method (README.json)
+            let (method, route) = stem.split_once("__").ok_or("no `// METHOD /path` header and no METHOD__path file name")?;
+            if !known_method(method) {
+                return Err(format!("unknown method '{}'", method));
             }
+            (method.to_string(), route.replace("__", "/"), content)
+        }
+    };
+
+    let method = method.to_uppercase();
+    let route = if route.starts_with('/') { route } else { format!("/{}", route) };
+    Ok(MockApi {
+        id: server::mock_key(&method, &route, None, None, None, None, None),
+        path: route,
+        method,
+        response_body: body,
+        status_code: 200,
+        response_type: response_type.to_string(),
+        ..Default::default()
+    })
+}
+
+// Seeded mocks are merged over the stored ones; they are not written back to mocks.json
+// until something else triggers a save.
+fn load_seed_mocks(dir: &Path) -> Vec<MockApi> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut seeded = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match parse_seed_file(&path) {
+            Ok(mock) => seeded.push(mock),
+            Err(e) => println!("Skipping seed file {}: {}", path.display(), e),
         }
     }
-    HashMap::new()
+    seeded
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -50,235 +240,1758 @@ fn get_db_config_path(app_handle: &AppHandle) -> Option<PathBuf> {
 
 fn save_db_configs(app_handle: &AppHandle, configs: &Vec<DbConfig>) -> Result<(), String> {
     if let Some(path) = get_db_config_path(app_handle) {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-        let json = serde_json::to_string_pretty(configs).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
+        save_json_file(app_handle, &path, configs)?;
     }
     Ok(())
 }
 
 fn load_db_configs(app_handle: &AppHandle) -> Vec<DbConfig> {
-    if let Some(path) = get_db_config_path(app_handle) {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(configs) = serde_json::from_str(&content) {
-                    return configs;
+    get_db_config_path(app_handle)
+        .and_then(|path| load_json_file(app_handle, &path))
+        .unwrap_or_default()
+}
+
+fn get_server_config_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path().app_data_dir().ok().map(|p| p.join(SERVER_CONFIG_FILE_NAME))
+}
+
+fn save_server_config(app_handle: &AppHandle, config: &ServerConfig) -> Result<(), String> {
+    if let Some(path) = get_server_config_path(app_handle) {
+        save_json_file(app_handle, &path, config)?;
+    }
+    Ok(())
+}
+
+fn load_server_config(app_handle: &AppHandle) -> ServerConfig {
+    get_server_config_path(app_handle)
+        .and_then(|path| load_json_file(app_handle, &path))
+        .unwrap_or_default()
+}
+
+fn get_environments_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path().app_data_dir().ok().map(|p| p.join(ENVIRONMENTS_FILE_NAME))
+}
+
+fn save_environments(app_handle: &AppHandle, environments: &HashMap<String, Environment>) -> Result<(), String> {
+    if let Some(path) = get_environments_path(app_handle) {
+        save_json_file(app_handle, &path, environments)?;
+    }
+    Ok(())
+}
+
+fn load_environments(app_handle: &AppHandle) -> HashMap<String, Environment> {
+    get_environments_path(app_handle)
+        .and_then(|path| load_json_file(app_handle, &path))
+        .unwrap_or_default()
+}
+
+fn get_fixtures_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path().app_data_dir().ok().map(|p| p.join(FIXTURES_FILE_NAME))
+}
+
+fn save_fixtures(app_handle: &AppHandle, fixtures: &HashMap<String, RequestFixture>) -> Result<(), String> {
+    if let Some(path) = get_fixtures_path(app_handle) {
+        save_json_file(app_handle, &path, fixtures)?;
+    }
+    Ok(())
+}
+
+fn load_fixtures(app_handle: &AppHandle) -> HashMap<String, RequestFixture> {
+    get_fixtures_path(app_handle)
+        .and_then(|path| load_json_file(app_handle, &path))
+        .unwrap_or_default()
+}
+
+// Open the SQLite log store if it's enabled and not open yet
+fn ensure_log_store(app_handle: &AppHandle, state: &AppState) {
+    if state.config.lock().unwrap().log_store != "sqlite" {
+        return;
+    }
+    let mut log_db = state.log_db.lock().unwrap();
+    if log_db.is_some() {
+        return;
+    }
+    let Ok(dir) = app_handle.path().app_data_dir() else {
+        return;
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        println!("Failed to create log store directory: {}", e);
+        return;
+    }
+
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(dir.join(LOG_DB_FILE_NAME))
+        .create_if_missing(true);
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(4)
+        .connect_lazy_with(options);
+    *log_db = Some(pool.clone());
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server::init_log_store(&pool).await {
+            println!("Failed to initialise log store: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+async fn get_server_config(state: State<'_, AppState>) -> Result<ServerConfig, String> {
+    let config = state.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.clone())
+}
+
+#[tauri::command]
+async fn update_server_config(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    config: ServerConfig,
+) -> Result<(), String> {
+    // Build first so a bad CA path is reported before anything is applied
+    let proxy_client = server::build_proxy_client(&config)?;
+
+    // Update state
+    {
+        let mut state_config = state.config.lock().map_err(|e| e.to_string())?;
+        *state_config = config.clone();
+    }
+    *state.proxy_client.lock().map_err(|e| e.to_string())? = proxy_client;
+    
+    // Save to file
+    save_server_config(&app_handle, &config)?;
+
+    ensure_log_store(&app_handle, &state);
+    
+    // Restart server logic handled in frontend or separate command?
+    // Ideally we signal the server thread to restart.
+    // For now, let's just emit an event or rely on the user to restart?
+    // Actually, we can implement a restart mechanism using channels.
+    
+    app_handle.emit("server-config-changed", ()).map_err(|e| e.to_string())?;
+    
+    Ok(())
+}
+
+use sqlx::any::AnyPoolOptions;
+use std::time::Duration;
+
+// The pool kept for a saved DB connection; nothing connects until it's first used
+fn lazy_pool(url: &str) -> Result<sqlx::Pool<sqlx::Any>, sqlx::Error> {
+    AnyPoolOptions::new()
+        .max_connections(20)
+        .acquire_timeout(Duration::from_secs(30))
+        .connect_lazy(url)
+}
+
+#[tauri::command]
+async fn add_db_connection(app_handle: AppHandle, state: State<'_, AppState>, name: String, url: String) -> Result<(), String> {
+    // Validate connection first
+    let _ = AnyPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(10))
+        .connect(&url)
+        .await
+        .map_err(|e| format!("Failed to connect: {}", e))?;
+
+    // If successful, create a lazy pool for storage
+    let pool = lazy_pool(&url).map_err(|e| format!("Failed to create pool: {}", e))?;
+    
+    let mut connections = state.db_connections.lock().map_err(|e| e.to_string())?;
+    connections.insert(name.clone(), pool);
+    
+    // Save config
+    // We need to reconstruct the list from current connections + maybe a separate config storage?
+    // Connection pool cannot be serialized. We need to store configs separately.
+    // Let's load existing configs, update, and save.
+    let mut configs = load_db_configs(&app_handle);
+    // Remove existing if same name
+    configs.retain(|c| c.name != name);
+    configs.push(DbConfig { name, url });
+    save_db_configs(&app_handle, &configs)?;
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn remove_db_connection(app_handle: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let mut connections = state.db_connections.lock().map_err(|e| e.to_string())?;
+    connections.remove(&name);
+    state.db_status.lock().map_err(|e| e.to_string())?.remove(&name);
+    
+    let mut configs = load_db_configs(&app_handle);
+    configs.retain(|c| c.name != name);
+    save_db_configs(&app_handle, &configs)?;
+    
+    Ok(())
+}
+
+#[tauri::command]
+fn get_db_connections(app_handle: AppHandle) -> Result<Vec<DbConfig>, String> {
+    Ok(load_db_configs(&app_handle))
+}
+
+// How often the background monitor pings the pools and rebuilds dead ones
+const DB_HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+const DB_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn ping_db(pool: &sqlx::Pool<sqlx::Any>) -> bool {
+    matches!(tokio::time::timeout(DB_PING_TIMEOUT, sqlx::query("SELECT 1").execute(pool)).await, Ok(Ok(_)))
+}
+
+// Remember a connection's liveness, emitting db-connection-status when it changes
+fn record_db_status(app_handle: &AppHandle, state: &AppState, name: &str, up: bool) {
+    let previous = state.db_status.lock().unwrap().insert(name.to_string(), up);
+    if previous != Some(up) {
+        println!("DB connection '{}' is {}", name, if up { "up" } else { "down" });
+        let _ = app_handle.emit("db-connection-status", serde_json::json!({ "name": name, "up": up }));
+    }
+}
+
+// Ping every pool with SELECT 1, sorted by connection name
+async fn ping_db_connections(app_handle: &AppHandle, state: &AppState) -> Vec<(String, bool)> {
+    // Clone the pools out so the lock isn't held across the pings
+    let mut pools: Vec<(String, sqlx::Pool<sqlx::Any>)> = state.db_connections.lock().unwrap()
+        .iter()
+        .map(|(name, pool)| (name.clone(), pool.clone()))
+        .collect();
+    pools.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut statuses = Vec::new();
+    for (name, pool) in pools {
+        let up = ping_db(&pool).await;
+        record_db_status(app_handle, state, &name, up);
+        statuses.push((name, up));
+    }
+    statuses
+}
+
+#[tauri::command]
+async fn check_db_connections(app_handle: AppHandle, state: State<'_, AppState>) -> Result<Vec<(String, bool)>, String> {
+    Ok(ping_db_connections(&app_handle, &state).await)
+}
+
+// Background task: periodically ping every saved connection, and build a pool from
+// db_connections.json for connections that have none or whose pool has an outdated URL.
+// A lazy pool reconnects by itself once its database is back, so being down isn't a reason.
+async fn monitor_db_connections(app_handle: AppHandle, state: AppState) {
+    // The first check waits a full interval, after the startup pools are in place
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + DB_HEALTH_INTERVAL, DB_HEALTH_INTERVAL);
+    loop {
+        interval.tick().await;
+        ping_db_connections(&app_handle, &state).await;
+        for config in load_db_configs(&app_handle) {
+            let current_url = state.db_connections.lock().unwrap()
+                .get(&config.name)
+                .map(|pool| pool.connect_options().database_url.clone());
+            if current_url.is_some_and(|current| url::Url::parse(&config.url).is_ok_and(|saved| saved == current)) {
+                continue;
+            }
+            let pool = match lazy_pool(&config.url) {
+                Ok(pool) => pool,
+                Err(e) => {
+                    println!("Failed to recreate pool for DB '{}': {}", config.name, e);
+                    continue;
                 }
+            };
+            let up = ping_db(&pool).await;
+            state.db_connections.lock().unwrap().insert(config.name.clone(), pool);
+            record_db_status(&app_handle, &state, &config.name, up);
+        }
+    }
+}
+
+#[tauri::command]
+async fn test_db_connection(url: String) -> Result<String, String> {
+    // Manually install SQLx drivers
+    sqlx::any::install_default_drivers();
+    
+    match AnyPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(10))
+        .connect(&url)
+        .await 
+    {
+        Ok(_) => Ok("Connection successful".to_string()),
+        Err(e) => Err(format!("Connection failed: {}", e)),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct DbUrlInfo {
+    driver: String,
+    host: Option<String>,
+    port: Option<u16>,
+    database: Option<String>,
+}
+
+// Parse-only check of a connection URL; unlike test_db_connection this does no network I/O
+#[tauri::command]
+fn validate_db_url(url: String) -> Result<DbUrlInfo, String> {
+    use std::str::FromStr;
+
+    let scheme = url.split(':').next().unwrap_or("").to_lowercase();
+    match scheme.as_str() {
+        "postgres" | "postgresql" => {
+            let opts = sqlx::postgres::PgConnectOptions::from_str(&url).map_err(|e| e.to_string())?;
+            Ok(DbUrlInfo {
+                driver: "postgres".to_string(),
+                host: Some(opts.get_host().to_string()),
+                port: Some(opts.get_port()),
+                database: opts.get_database().map(str::to_string),
+            })
+        }
+        "mysql" | "mariadb" => {
+            let opts = sqlx::mysql::MySqlConnectOptions::from_str(&url).map_err(|e| e.to_string())?;
+            Ok(DbUrlInfo {
+                driver: "mysql".to_string(),
+                host: Some(opts.get_host().to_string()),
+                port: Some(opts.get_port()),
+                database: opts.get_database().map(str::to_string),
+            })
+        }
+        "sqlite" => {
+            let opts = sqlx::sqlite::SqliteConnectOptions::from_str(&url).map_err(|e| e.to_string())?;
+            Ok(DbUrlInfo {
+                driver: "sqlite".to_string(),
+                host: None,
+                port: None,
+                database: Some(opts.get_filename().display().to_string()),
+            })
+        }
+        "" => Err("Missing URL scheme".to_string()),
+        other => Err(format!("Unsupported database driver '{}'", other)),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct CoverageItem {
+    method: String,
+    path: String,
+    operation_id: Option<String>,
+    // Mocks that serve this operation; empty for missing operations
+    mock_ids: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct CoverageReport {
+    covered: Vec<CoverageItem>,
+    missing: Vec<CoverageItem>,
+    // Mocks that don't serve any operation in the spec
+    unmatched_mocks: Vec<String>,
+}
+
+const OPENAPI_METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+// Compare the current mocks against the operations of an OpenAPI (or Swagger 2) JSON spec
+#[tauri::command]
+fn coverage_report(state: State<'_, AppState>, spec_json: String) -> Result<CoverageReport, String> {
+    let spec: serde_json::Value = serde_json::from_str(&spec_json).map_err(|e| format!("Invalid spec: {}", e))?;
+    let paths = spec
+        .get("paths")
+        .and_then(|p| p.as_object())
+        .ok_or_else(|| "Spec has no paths object".to_string())?;
+
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mut used = std::collections::HashSet::new();
+    let mut report = CoverageReport { covered: Vec::new(), missing: Vec::new(), unmatched_mocks: Vec::new() };
+
+    for (path, item) in paths {
+        let Some(item) = item.as_object() else { continue };
+        for method in OPENAPI_METHODS {
+            let Some(operation) = item.get(method) else { continue };
+            let method = method.to_uppercase();
+            let mut mock_ids: Vec<String> = mocks
+                .values()
+                .filter(|m| server::mock_covers_route(m, &method, path))
+                .map(|m| m.id.clone())
+                .collect();
+            mock_ids.sort();
+            used.extend(mock_ids.iter().cloned());
+
+            let entry = CoverageItem {
+                method,
+                path: path.clone(),
+                operation_id: operation.get("operationId").and_then(|v| v.as_str()).map(str::to_string),
+                mock_ids,
+            };
+            if entry.mock_ids.is_empty() {
+                report.missing.push(entry);
+            } else {
+                report.covered.push(entry);
             }
         }
     }
-    Vec::new()
+
+    report.unmatched_mocks = mocks.keys().filter(|id| !used.contains(*id)).cloned().collect();
+    report.unmatched_mocks.sort();
+    Ok(report)
 }
 
-fn get_server_config_path(app_handle: &AppHandle) -> Option<PathBuf> {
-    app_handle.path().app_data_dir().ok().map(|p| p.join(SERVER_CONFIG_FILE_NAME))
+#[derive(serde::Serialize)]
+struct OpenApiImportSummary {
+    imported: usize,
+    // "METHOD /path: <reason>" for operations that still need a mock
+    skipped: Vec<String>,
+}
+
+// Create a json mock per operation of an OpenAPI 3 (or Swagger 2) JSON spec, using the
+// first 2xx (else default) response's example, or a value derived from its schema.
+// "{id}" segments become ":id" params. Routes that are already mocked are left alone.
+#[tauri::command]
+fn import_openapi(app_handle: AppHandle, state: State<'_, AppState>, spec_json: String) -> Result<OpenApiImportSummary, String> {
+    let spec: serde_json::Value = serde_json::from_str(&spec_json).map_err(|e| format!("Invalid spec: {}", e))?;
+    let paths = spec
+        .get("paths")
+        .and_then(|p| p.as_object())
+        .ok_or_else(|| "Spec has no paths object".to_string())?;
+
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mut summary = OpenApiImportSummary { imported: 0, skipped: Vec::new() };
+
+    for (spec_path, item) in paths {
+        let Some(item) = item.as_object() else { continue };
+        let templated = spec_path.contains('{');
+        let path = spec_path
+            .split('/')
+            .map(|segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => format!(":{}", name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        for method in OPENAPI_METHODS {
+            let Some(operation) = item.get(method) else { continue };
+            let method = method.to_uppercase();
+            let key = server::mock_key(&method, &path, None, None, None, None, None);
+            if mocks.contains_key(&key) {
+                summary.skipped.push(format!("{} {}: already mocked", method, spec_path));
+                continue;
+            }
+            let Some((status_code, body)) = openapi_example_response(&spec, operation) else {
+                summary.skipped.push(format!("{} {}: no example or schema for a success response", method, spec_path));
+                continue;
+            };
+            let mock = MockApi {
+                id: key.clone(),
+                path: path.clone(),
+                method,
+                response_body: serde_json::to_string_pretty(&body).map_err(|e| e.to_string())?,
+                status_code,
+                response_type: "json".to_string(),
+                match_type: if templated { "param" } else { "exact" }.to_string(),
+                ..Default::default()
+            };
+            mocks.insert(key, mock);
+            summary.imported += 1;
+        }
+    }
+
+    if summary.imported > 0 {
+        save_mocks(&app_handle, &mocks)?;
+    }
+    Ok(summary)
+}
+
+// Status and body for the first 2xx response (or "default") that has an example or schema
+fn openapi_example_response(spec: &serde_json::Value, operation: &serde_json::Value) -> Option<(u16, serde_json::Value)> {
+    let responses = operation.get("responses")?.as_object()?;
+    let mut codes: Vec<&String> = responses.keys().filter(|code| code.starts_with('2')).collect();
+    codes.sort();
+    codes.extend(responses.keys().filter(|code| *code == "default"));
+
+    codes.into_iter().find_map(|code| {
+        let response = openapi_resolve(spec, responses.get(code)?);
+        let status = code.parse().unwrap_or(200);
+        // OpenAPI 3: content -> media type (JSON preferred) -> example / examples / schema
+        if let Some(content) = response.get("content").and_then(|c| c.as_object()) {
+            let media = content.get("application/json")
+                .or_else(|| content.iter().find(|(k, _)| k.contains("json")).map(|(_, v)| v))
+                .or_else(|| content.values().next())?;
+            let example = media.get("example").cloned()
+                .or_else(|| media.get("examples")?.as_object()?.values().next().map(|e| openapi_resolve(spec, e)).and_then(|e| e.get("value").cloned()))
+                .or_else(|| media.get("schema").map(|s| openapi_schema_example(spec, s, 0)));
+            return example.map(|body| (status, body));
+        }
+        // Swagger 2: examples keyed by mime type, or schema
+        let example = response.get("examples").and_then(|e| e.get("application/json")).cloned()
+            .or_else(|| response.get("schema").map(|s| openapi_schema_example(spec, s, 0)));
+        example.map(|body| (status, body))
+    })
+}
+
+// Follows a local "#/..." $ref; anything else is returned unchanged
+fn openapi_resolve<'a>(spec: &'a serde_json::Value, value: &'a serde_json::Value) -> &'a serde_json::Value {
+    match value.get("$ref").and_then(|r| r.as_str()).and_then(|r| r.strip_prefix('#')) {
+        Some(pointer) => spec.pointer(pointer).unwrap_or(value),
+        None => value,
+    }
+}
+
+// Placeholder value shaped like the schema; recursion is cut off for self-referencing schemas
+fn openapi_schema_example(spec: &serde_json::Value, schema: &serde_json::Value, depth: usize) -> serde_json::Value {
+    use serde_json::Value;
+    let schema = openapi_resolve(spec, schema);
+    if depth > 8 {
+        return Value::Null;
+    }
+    if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+        return example.clone();
+    }
+    if let Some(first) = schema.get("enum").and_then(|e| e.as_array()).and_then(|e| e.first()) {
+        return first.clone();
+    }
+    if let Some(first) = ["allOf", "oneOf", "anyOf"].iter().find_map(|k| schema.get(*k)?.as_array()?.first()) {
+        return openapi_schema_example(spec, first, depth + 1);
+    }
+    match schema.get("type").and_then(|t| t.as_str()) {
+        Some("string") => Value::String("string".to_string()),
+        Some("integer") | Some("number") => Value::from(0),
+        Some("boolean") => Value::Bool(false),
+        Some("array") => match schema.get("items") {
+            Some(items) => Value::Array(vec![openapi_schema_example(spec, items, depth + 1)]),
+            None => Value::Array(Vec::new()),
+        },
+        _ => match schema.get("properties").and_then(|p| p.as_object()) {
+            Some(properties) => Value::Object(
+                properties.iter().map(|(k, v)| (k.clone(), openapi_schema_example(spec, v, depth + 1))).collect(),
+            ),
+            None => Value::Object(serde_json::Map::new()),
+        },
+    }
+}
+
+#[derive(serde::Serialize, Default)]
+struct PostmanImportSummary {
+    created: Vec<String>,
+    // "<item name>: <reason>"
+    skipped: Vec<String>,
+}
+
+// Create a mock per request in a Postman v2 collection (folders are walked recursively),
+// answering with the request's first saved example, or an empty 200 JSON object.
+// Host parts such as "{{baseUrl}}" are dropped and "{{var}}" path segments become ":var".
+#[tauri::command]
+fn import_postman(app_handle: AppHandle, state: State<'_, AppState>, collection_json: String) -> Result<PostmanImportSummary, String> {
+    let collection: serde_json::Value = serde_json::from_str(&collection_json).map_err(|e| format!("Invalid collection: {}", e))?;
+    let items = collection
+        .get("item")
+        .and_then(|i| i.as_array())
+        .ok_or_else(|| "Collection has no item array".to_string())?;
+
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mut summary = PostmanImportSummary::default();
+    import_postman_items(items, &mut mocks, &mut summary);
+
+    if !summary.created.is_empty() {
+        save_mocks(&app_handle, &mocks)?;
+    }
+    Ok(summary)
+}
+
+fn import_postman_items(items: &[serde_json::Value], mocks: &mut HashMap<String, MockApi>, summary: &mut PostmanImportSummary) {
+    for item in items {
+        let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("(unnamed)");
+        if let Some(children) = item.get("item").and_then(|i| i.as_array()) {
+            import_postman_items(children, mocks, summary);
+            continue;
+        }
+        let Some(request) = item.get("request") else {
+            summary.skipped.push(format!("{}: no request", name));
+            continue;
+        };
+        // A request may be just the URL string, which implies GET
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("GET").to_uppercase();
+        let url = if request.is_string() { Some(request) } else { request.get("url") };
+        let Some(path) = url.and_then(postman_path) else {
+            summary.skipped.push(format!("{}: no URL path", name));
+            continue;
+        };
+
+        let key = server::mock_key(&method, &path, None, None, None, None, None);
+        if mocks.contains_key(&key) {
+            summary.skipped.push(format!("{}: {} is already mocked", name, key));
+            continue;
+        }
+
+        let example = item.get("response").and_then(|r| r.as_array()).and_then(|r| r.first());
+        let status_code = example.and_then(|e| e.get("code")).and_then(|c| c.as_u64()).unwrap_or(200) as u16;
+        let body = example.and_then(|e| e.get("body")).and_then(|b| b.as_str()).unwrap_or("{}").to_string();
+        let response_type = if serde_json::from_str::<serde_json::Value>(&body).is_ok() {
+            "json"
+        } else if example.and_then(|e| e.get("_postman_previewlanguage")).and_then(|l| l.as_str()) == Some("html") {
+            "html"
+        } else {
+            "raw"
+        };
+
+        let mock = MockApi {
+            id: key.clone(),
+            match_type: if path.contains("/:") { "param" } else { "exact" }.to_string(),
+            path,
+            method,
+            response_body: body,
+            status_code,
+            response_type: response_type.to_string(),
+            ..Default::default()
+        };
+        summary.created.push(key.clone());
+        mocks.insert(key, mock);
+    }
+}
+
+// Path of a Postman URL (either the raw string or the object form) in mock syntax
+fn postman_path(url: &serde_json::Value) -> Option<String> {
+    let segments: Vec<String> = match url.get("path").and_then(|p| p.as_array()) {
+        Some(path) => path.iter().filter_map(|s| s.as_str().map(str::to_string)).collect(),
+        None => {
+            let raw = url.as_str().or_else(|| url.get("raw")?.as_str())?;
+            let raw = raw.split(['?', '#']).next().unwrap_or_default();
+            let segments: Vec<&str> = match raw.split_once("://") {
+                // Drop the host
+                Some((_, rest)) => rest.split('/').skip(1).collect(),
+                None => {
+                    let mut segments: Vec<&str> = raw.split('/').collect();
+                    // "{{baseUrl}}/users" or "example.com/users": the first segment is the host
+                    if segments.first().is_some_and(|s| !s.is_empty()) {
+                        segments.remove(0);
+                    }
+                    segments
+                }
+            };
+            segments.into_iter().map(str::to_string).collect()
+        }
+    };
+    let segments: Vec<String> = segments
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.strip_prefix("{{").and_then(|s| s.strip_suffix("}}")) {
+            Some(var) => format!(":{}", var),
+            None => s,
+        })
+        .collect();
+    Some(format!("/{}", segments.join("/")))
+}
+
+// Blank means no body constraint; valid JSON is stored compacted so equal matches share a key
+fn normalize_body_match(body_match: Option<String>) -> Option<String> {
+    body_match
+        .filter(|b| !b.trim().is_empty())
+        .map(|b| serde_json::from_str::<serde_json::Value>(&b).map(|v| v.to_string()).unwrap_or(b))
+}
+
+// Header names are case-insensitive, so they're stored lowercase like HeaderName
+fn normalize_header_match(header_match: Option<HashMap<String, String>>) -> Option<HashMap<String, String>> {
+    header_match
+        .map(|h| h.into_iter().map(|(k, v)| (k.trim().to_ascii_lowercase(), v)).collect::<HashMap<_, _>>())
+        .filter(|h| !h.is_empty())
+}
+
+// Checks that can't be expressed in the type system; run before a mock is stored
+fn validate_mock(mock: &MockApi) -> Result<(), String> {
+    if !matches!(mock.match_type.as_str(), "exact" | "param" | "regex") {
+        return Err(format!("Unknown match_type '{}'", mock.match_type));
+    }
+    if mock.match_type == "regex" {
+        regex::Regex::new(&mock.path).map_err(|e| format!("Invalid path regex: {}", e))?;
+    }
+    if let Some(schema) = mock.expected_request.as_ref().and_then(|e| e.body_schema.as_ref()) {
+        jsonschema::validator_for(schema).map_err(|e| format!("Invalid body_schema: {}", e))?;
+    }
+    if let Some(schema) = &mock.request_schema {
+        let schema = serde_json::from_str::<serde_json::Value>(schema).map_err(|e| format!("Invalid request_schema JSON: {}", e))?;
+        jsonschema::validator_for(&schema).map_err(|e| format!("Invalid request_schema: {}", e))?;
+    }
+    if let Some(body_match) = &mock.body_match {
+        serde_json::from_str::<serde_json::Value>(body_match).map_err(|e| format!("Invalid body_match JSON: {}", e))?;
+    }
+    for (name, value) in &mock.proxy_add_headers {
+        axum::http::HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("Invalid proxy header name '{}'", name))?;
+        axum::http::HeaderValue::from_str(value).map_err(|_| format!("Invalid value for proxy header '{}'", name))?;
+    }
+    for name in mock.header_match.iter().flat_map(|h| h.keys()) {
+        axum::http::HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header_match name '{}'", name))?;
+    }
+    for name in &mock.proxy_remove_headers {
+        axum::http::HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("Invalid proxy header name '{}'", name))?;
+    }
+    if mock.response_type == "file" && mock.response_body.trim().is_empty() {
+        return Err("File response needs a file path in the response body".to_string());
+    }
+    if !matches!(mock.proxy_balance.as_str(), "" | "round-robin" | "random") {
+        return Err(format!("Unknown proxy_balance '{}'", mock.proxy_balance));
+    }
+    if mock.response_type == "json-stream" {
+        let bodies = std::iter::once(&mock.response_body).chain(mock.weighted_responses.iter().map(|r| &r.body));
+        for body in bodies {
+            serde_json::from_str::<Vec<serde_json::Value>>(body)
+                .map_err(|e| format!("json-stream body must be a JSON array: {}", e))?;
+        }
+    }
+    if mock.response_type == "websocket" && !mock.response_body.trim().is_empty() {
+        let script = serde_json::from_str::<Vec<server::WsScriptMessage>>(&mock.response_body)
+            .map_err(|e| format!("websocket body must be empty (echo) or a JSON array of messages: {}", e))?;
+        if let Some(step) = script.iter().find(|step| step.delay_ms > server::MAX_WS_SCRIPT_DELAY_MS) {
+            return Err(format!("websocket delay_ms {} exceeds the maximum of {}", step.delay_ms, server::MAX_WS_SCRIPT_DELAY_MS));
+        }
+    }
+    if let Some(origin) = mock.cors_origin.as_deref() {
+        axum::http::HeaderValue::from_str(origin).map_err(|_| format!("Invalid cors_origin '{}'", origin))?;
+    }
+    if mock.response_type == "binary" {
+        use base64::Engine;
+        let bodies = std::iter::once(&mock.response_body).chain(mock.weighted_responses.iter().map(|r| &r.body));
+        for body in bodies {
+            base64::engine::general_purpose::STANDARD.decode(body.trim())
+                .map_err(|e| format!("binary body must be base64: {}", e))?;
+        }
+    }
+    if let Some(content_type) = &mock.content_type {
+        axum::http::HeaderValue::from_str(content_type).map_err(|_| format!("Invalid content_type '{}'", content_type))?;
+    }
+    if mock.response_type == "sequence" {
+        let steps: Vec<server::SequenceStep> = serde_json::from_str(&mock.response_body)
+            .map_err(|e| format!("sequence body must be a JSON array of steps: {}", e))?;
+        if steps.is_empty() {
+            return Err("sequence body needs at least one step".to_string());
+        }
+        if let Some(step) = steps.iter().find(|s| !matches!(s.response_type.as_str(), "json" | "html" | "raw")) {
+            return Err(format!("Unsupported sequence step response_type '{}'", step.response_type));
+        }
+    }
+    Ok(())
+}
+
+// Non-fatal warnings about how `mock` interacts with the other mocks. On the same exact
+// path a method-specific mock always beats ANY, so ANY only answers the remaining methods.
+fn validate_mocks(mocks: &HashMap<String, MockApi>, mock: &MockApi) -> Vec<String> {
+    let same_route = |other: &&MockApi| {
+        other.id != mock.id
+            && other.path == mock.path
+            && other.match_type == mock.match_type
+            && other.host_match == mock.host_match
+            && other.query_match == mock.query_match
+            && other.body_match == mock.body_match
+            && other.header_match == mock.header_match
+            && other.port == mock.port
+    };
+    let mut warnings = Vec::new();
+    if mock.method == "ANY" {
+        let mut methods: Vec<&str> = mocks.values().filter(same_route).filter(|m| m.method != "ANY").map(|m| m.method.as_str()).collect();
+        if !methods.is_empty() {
+            methods.sort();
+            warnings.push(format!(
+                "ANY {} is shadowed for {} by method-specific mocks on the same path",
+                mock.path,
+                methods.join(", ")
+            ));
+        }
+    } else if mocks.values().filter(same_route).any(|m| m.method == "ANY") {
+        warnings.push(format!(
+            "{} {} takes precedence over ANY {} for {} requests",
+            mock.method, mock.path, mock.path, mock.method
+        ));
+    }
+    warnings
+}
+
+// The fields of a mock as the mock form sends them to add_mock_api and update_mock_api.
+// The optional ones default to unset; on update, unset keeps the stored value.
+#[derive(serde::Deserialize)]
+struct MockApiInput {
+    path: String,
+    method: String,
+    response_body: String,
+    status_code: u16,
+    response_type: String,
+    match_type: Option<String>,
+    single_use: Option<bool>,
+    exhausted_status: Option<u16>,
+    weighted_responses: Option<Vec<WeightedResponse>>,
+    proxy_host: Option<String>,
+    expected_request: Option<ExpectedRequest>,
+    toggle_ref: Option<ToggleRef>,
+    stream_interval_ms: Option<u64>,
+    host_match: Option<String>,
+    proxy_targets: Option<Vec<String>>,
+    proxy_balance: Option<String>,
+    proxy_failover: Option<bool>,
+    query_match: Option<HashMap<String, String>>,
+    body_match: Option<String>,
+    templated: Option<bool>,
+    enabled: Option<bool>,
+    proxy_add_headers: Option<HashMap<String, String>>,
+    proxy_remove_headers: Option<Vec<String>>,
+    proxy_rewrite: Option<(String, String)>,
+    record: Option<bool>,
+    group: Option<String>,
+    request_schema: Option<String>,
+    header_match: Option<HashMap<String, String>>,
+    content_type: Option<String>,
+    port: Option<u16>,
+    cors_origin: Option<String>,
+    cors_allow_credentials: Option<bool>,
+}
+
+#[tauri::command]
+fn add_mock_api(app_handle: AppHandle, state: State<'_, AppState>, mock: MockApiInput) -> Result<Vec<String>, String> {
+    let MockApiInput {
+        path, method, response_body, status_code, response_type, match_type, single_use,
+        exhausted_status, weighted_responses, proxy_host, expected_request, toggle_ref,
+        stream_interval_ms, host_match, proxy_targets, proxy_balance, proxy_failover, query_match,
+        body_match, templated, enabled, proxy_add_headers, proxy_remove_headers, proxy_rewrite,
+        record, group, request_schema, header_match, content_type, port, cors_origin,
+        cors_allow_credentials,
+    } = mock;
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let method = method.to_uppercase();
+    // Ensure path starts with /
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    let host_match = host_match.map(|h| h.trim().to_ascii_lowercase()).filter(|h| !h.is_empty());
+    let query_match = query_match.filter(|q| !q.is_empty());
+    let body_match = normalize_body_match(body_match);
+    let header_match = normalize_header_match(header_match);
+    let port = port.filter(|p| *p != 0);
+    let key = server::mock_key(&method, &path, host_match.as_deref(), query_match.as_ref(), body_match.as_deref(), header_match.as_ref(), port);
+    
+    let mock = MockApi {
+        id: key.clone(),
+        path: path.clone(),
+        method,
+        response_body,
+        status_code,
+        response_type,
+        match_type: match_type.unwrap_or_else(|| "exact".to_string()),
+        single_use: single_use.unwrap_or(false),
+        exhausted_status: exhausted_status.filter(|s| *s != 0),
+        weighted_responses: weighted_responses.unwrap_or_default(),
+        proxy_host: proxy_host.map(|h| h.trim().to_string()).filter(|h| !h.is_empty()),
+        enabled: enabled.unwrap_or(true),
+        group: group.map(|g| g.trim().to_string()).filter(|g| !g.is_empty()),
+        expected_request: expected_request.filter(|e| !e.is_empty()),
+        toggle_ref: toggle_ref.filter(|t| !t.name.trim().is_empty()),
+        stream_interval_ms: stream_interval_ms.filter(|ms| *ms != 0),
+        host_match,
+        proxy_targets: proxy_targets.unwrap_or_default(),
+        proxy_balance: proxy_balance.unwrap_or_default(),
+        proxy_failover: proxy_failover.unwrap_or(false),
+        query_match,
+        body_match,
+        header_match,
+        templated: templated.unwrap_or(false),
+        proxy_add_headers: proxy_add_headers.unwrap_or_default(),
+        proxy_remove_headers: proxy_remove_headers.unwrap_or_default(),
+        proxy_rewrite: proxy_rewrite.filter(|(find, _)| !find.is_empty()),
+        record: record.unwrap_or(false),
+        request_schema: request_schema.filter(|s| !s.trim().is_empty()),
+        content_type: content_type.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+        port,
+        cors_origin: cors_origin.map(|o| o.trim().to_string()).filter(|o| !o.is_empty()),
+        cors_allow_credentials,
+        priority: 0,
+    };
+    validate_mock(&mock)?;
+    let warnings = validate_mocks(&mocks, &mock);
+    
+    mocks.insert(key, mock);
+    save_mocks(&app_handle, &mocks)?;
+    Ok(warnings)
+}
+
+#[tauri::command]
+fn get_mock_apis(state: State<'_, AppState>, filter: Option<MockFilter>) -> Result<Vec<MockApi>, String> {
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let filter = filter.unwrap_or_default();
+    Ok(mocks.values().filter(|m| filter.matches(m)).cloned().collect())
+}
+
+// Case-insensitive substring search over path and response body, sorted by path
+#[tauri::command]
+fn search_mock_apis(state: State<'_, AppState>, query: String, method: Option<String>, response_type: Option<String>) -> Result<Vec<MockApi>, String> {
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let query = query.to_lowercase();
+    let mut results: Vec<MockApi> = mocks
+        .values()
+        .filter(|m| query.is_empty() || m.path.to_lowercase().contains(&query) || m.response_body.to_lowercase().contains(&query))
+        .filter(|m| method.as_ref().is_none_or(|method| m.method.eq_ignore_ascii_case(method)))
+        .filter(|m| response_type.as_ref().is_none_or(|t| m.response_type == *t))
+        .cloned()
+        .collect();
+    results.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.id.cmp(&b.id)));
+    Ok(results)
+}
+
+// Apply "enable", "disable" or "delete" to every mock matching the filter, saving once.
+// Returns the number of mocks affected.
+#[tauri::command]
+fn bulk_update_mocks(app_handle: AppHandle, state: State<'_, AppState>, filter: MockFilter, action: String) -> Result<usize, String> {
+    if filter.is_empty() {
+        return Err("Filter must specify at least one of group, path_prefix or method".to_string());
+    }
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+
+    let affected = match action.as_str() {
+        "enable" | "disable" => {
+            let enabled = action == "enable";
+            let mut count = 0;
+            for mock in mocks.values_mut().filter(|m| filter.matches(m)) {
+                mock.enabled = enabled;
+                count += 1;
+            }
+            count
+        }
+        "delete" => {
+            let before = mocks.len();
+            mocks.retain(|_, m| !filter.matches(m));
+            before - mocks.len()
+        }
+        other => return Err(format!("Unknown action '{}'", other)),
+    };
+
+    if affected > 0 {
+        save_mocks(&app_handle, &mocks)?;
+    }
+    Ok(affected)
+}
+
+// Copy a mock to "<path>-copy" (then "-copy-2", "-copy-3", ...) so the key is free
+#[tauri::command]
+fn duplicate_mock_api(app_handle: AppHandle, state: State<'_, AppState>, id: String) -> Result<MockApi, String> {
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let original = mocks.get(&id).cloned().ok_or_else(|| format!("Mock not found: {}", id))?;
+    // A suffix would change what a regex matches, and the id is derived from the path
+    if original.match_type == "regex" {
+        return Err("Regex mocks can't be duplicated; create the copy with add_mock_api instead".to_string());
+    }
+
+    // The suffix goes before a trailing wildcard so the copy is still a prefix mock
+    let (base, wildcard) = match original.path.strip_suffix('*') {
+        Some(base) => (base, "*"),
+        None => (original.path.as_str(), ""),
+    };
+    let mut copy = original.clone();
+    for n in 1.. {
+        copy.path = if n == 1 { format!("{}-copy{}", base, wildcard) } else { format!("{}-copy-{}{}", base, n, wildcard) };
+        copy.id = server::mock_key(&copy.method, &copy.path, copy.host_match.as_deref(), copy.query_match.as_ref(), copy.body_match.as_deref(), copy.header_match.as_ref(), copy.port);
+        if !mocks.contains_key(&copy.id) {
+            break;
+        }
+    }
+
+    mocks.insert(copy.id.clone(), copy.clone());
+    save_mocks(&app_handle, &mocks)?;
+    Ok(copy)
+}
+
+// Disabled mocks are skipped during matching but kept, and still listed by get_mock_apis
+#[tauri::command]
+fn set_mock_enabled(app_handle: AppHandle, state: State<'_, AppState>, id: String, enabled: bool) -> Result<(), String> {
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mock = mocks.get_mut(&id).ok_or_else(|| format!("Mock not found: {}", id))?;
+    mock.enabled = enabled;
+    save_mocks(&app_handle, &mocks)?;
+    Ok(())
+}
+
+// Raise or lower a mock against others matching the same requests (higher wins, default 0)
+#[tauri::command]
+fn set_mock_priority(app_handle: AppHandle, state: State<'_, AppState>, id: String, priority: i32) -> Result<(), String> {
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mock = mocks.get_mut(&id).ok_or_else(|| format!("Mock not found: {}", id))?;
+    mock.priority = priority;
+    save_mocks(&app_handle, &mocks)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_mock_api(state: State<'_, AppState>, id: String) -> Result<MockApi, String> {
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    mocks.get(&id).cloned().ok_or_else(|| format!("Mock not found: {}", id))
+}
+
+// Re-arm single-use mocks and rewind "sequence" mocks, for one mock or all of them
+#[tauri::command]
+fn reset_mock_sequence(state: State<'_, AppState>, id: Option<String>) -> Result<(), String> {
+    let mut counters = state.mock_counters.lock().map_err(|e| e.to_string())?;
+    let mut positions = state.sequence_positions.lock().map_err(|e| e.to_string())?;
+    match id {
+        Some(id) => {
+            counters.remove(&id);
+            positions.remove(&id);
+        }
+        None => {
+            counters.clear();
+            positions.clear();
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn remove_mock_api(app_handle: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    mocks.remove(&id);
+    save_mocks(&app_handle, &mocks)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn update_mock_api(app_handle: AppHandle, state: State<'_, AppState>, id: String, mock: MockApiInput) -> Result<Vec<String>, String> {
+    let MockApiInput {
+        path, method, response_body, status_code, response_type, match_type, single_use,
+        exhausted_status, weighted_responses, proxy_host, expected_request, toggle_ref,
+        stream_interval_ms, host_match, proxy_targets, proxy_balance, proxy_failover, query_match,
+        body_match, templated, enabled, proxy_add_headers, proxy_remove_headers, proxy_rewrite,
+        record, group, request_schema, header_match, content_type, port, cors_origin,
+        cors_allow_credentials,
+    } = mock;
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    
+    // If ID (method + path) changed, we need to remove the old one
+    // But since ID is the key, and user might change method/path, 
+    // we effectively do a remove + add, but frontend will pass the 'old' ID.
+    // Optional settings that aren't passed keep their previous values.
+    let previous = mocks.get(&id).cloned().unwrap_or_default();
+    
+    let method = method.to_uppercase();
+    // Ensure path starts with /
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    // Pass an empty host_match to make the mock apply to any host again
+    let host_match = host_match.or(previous.host_match).map(|h| h.trim().to_ascii_lowercase()).filter(|h| !h.is_empty());
+    // Likewise an empty query_match removes the query constraints
+    let query_match = query_match.or(previous.query_match).filter(|q| !q.is_empty());
+    // And an empty body_match matches any body again
+    let body_match = normalize_body_match(body_match.or(previous.body_match));
+    // An empty header_match removes the header constraints
+    let header_match = normalize_header_match(header_match.or(previous.header_match));
+    // Port 0 unbinds the mock from its server instance
+    let port = port.or(previous.port).filter(|p| *p != 0);
+    let key = server::mock_key(&method, &path, host_match.as_deref(), query_match.as_ref(), body_match.as_deref(), header_match.as_ref(), port);
+    
+    let mock = MockApi {
+        id: key.clone(),
+        path,
+        method,
+        response_body,
+        status_code,
+        response_type,
+        match_type: match_type.unwrap_or(previous.match_type),
+        single_use: single_use.unwrap_or(previous.single_use),
+        // 0 removes the exhausted_status, so a spent single-use mock stops matching again
+        exhausted_status: exhausted_status.or(previous.exhausted_status).filter(|s| *s != 0),
+        weighted_responses: weighted_responses.unwrap_or(previous.weighted_responses),
+        // An empty proxy_host sends the target's own Host again
+        proxy_host: proxy_host.or(previous.proxy_host).map(|h| h.trim().to_string()).filter(|h| !h.is_empty()),
+        enabled: enabled.unwrap_or(previous.enabled),
+        // An empty group moves the mock back to ungrouped
+        group: group.or(previous.group).map(|g| g.trim().to_string()).filter(|g| !g.is_empty()),
+        // An empty contract (no headers, query or schema) removes it
+        expected_request: expected_request.or(previous.expected_request).filter(|e| !e.is_empty()),
+        // A toggle_ref with an empty name detaches the mock from its toggle
+        toggle_ref: toggle_ref.or(previous.toggle_ref).filter(|t| !t.name.trim().is_empty()),
+        // 0 removes the pause between elements
+        stream_interval_ms: stream_interval_ms.or(previous.stream_interval_ms).filter(|ms| *ms != 0),
+        host_match,
+        proxy_targets: proxy_targets.unwrap_or(previous.proxy_targets),
+        proxy_balance: proxy_balance.unwrap_or(previous.proxy_balance),
+        proxy_failover: proxy_failover.unwrap_or(previous.proxy_failover),
+        query_match,
+        body_match,
+        header_match,
+        templated: templated.unwrap_or(previous.templated),
+        proxy_add_headers: proxy_add_headers.unwrap_or(previous.proxy_add_headers),
+        proxy_remove_headers: proxy_remove_headers.unwrap_or(previous.proxy_remove_headers),
+        // An empty pattern removes the rewrite
+        proxy_rewrite: proxy_rewrite.or(previous.proxy_rewrite).filter(|(find, _)| !find.is_empty()),
+        record: record.unwrap_or(previous.record),
+        // An empty schema stops validating the body
+        request_schema: request_schema.or(previous.request_schema).filter(|s| !s.trim().is_empty()),
+        // An empty content_type goes back to the default
+        content_type: content_type.or(previous.content_type).map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+        port,
+        cors_origin: cors_origin.or(previous.cors_origin).map(|o| o.trim().to_string()).filter(|o| !o.is_empty()),
+        cors_allow_credentials: cors_allow_credentials.or(previous.cors_allow_credentials),
+        priority: previous.priority,
+    };
+    validate_mock(&mock)?;
+
+    mocks.remove(&id);
+    let warnings = validate_mocks(&mocks, &mock);
+    // The per-id state of the old key would otherwise resurface if a mock gets that id again
+    if key != id {
+        state.mock_counters.lock().map_err(|e| e.to_string())?.remove(&id);
+        state.sequence_positions.lock().map_err(|e| e.to_string())?.remove(&id);
+        state.proxy_rotation.lock().map_err(|e| e.to_string())?.remove(&id);
+    }
+    mocks.insert(key, mock);
+    save_mocks(&app_handle, &mocks)?;
+    Ok(warnings)
+}
+
+use tauri::Emitter; // For emit
+use tokio::sync::broadcast;
+
+#[tauri::command]
+async fn get_request_logs(state: State<'_, AppState>) -> Result<Vec<RequestLog>, String> {
+    let logs = state.logs.lock().map_err(|e| e.to_string())?;
+    Ok(logs.iter().cloned().collect())
+}
+
+#[derive(serde::Serialize)]
+struct PathHits {
+    path: String,
+    hits: usize,
+}
+
+#[derive(serde::Serialize)]
+struct RequestStats {
+    total_requests: usize,
+    // Keyed by status class: "2xx", "4xx", ...
+    status_classes: std::collections::BTreeMap<String, usize>,
+    avg_duration_ms: f64,
+    p95_duration_ms: u64,
+    // Most requested paths, most hits first
+    top_paths: Vec<PathHits>,
+    // The log is full, so older requests have been dropped and aren't counted
+    limited_by_log_window: bool,
+    // Timestamp of the oldest request counted, if any
+    window_start: Option<u64>,
+}
+
+// Aggregate traffic over the in-memory request logs. `top` is the number of paths to
+// return (default 10).
+#[tauri::command]
+fn get_request_stats(state: State<'_, AppState>, top: Option<usize>) -> Result<RequestStats, String> {
+    let log_limit = state.config.lock().map_err(|e| e.to_string())?.log_limit.min(server::MAX_LOG_LIMIT);
+    let logs = state.logs.lock().map_err(|e| e.to_string())?;
+
+    let mut status_classes = std::collections::BTreeMap::new();
+    let mut path_hits: HashMap<&str, usize> = HashMap::new();
+    let mut durations: Vec<u64> = Vec::with_capacity(logs.len());
+    for log in logs.iter() {
+        *status_classes.entry(format!("{}xx", log.status_code / 100)).or_insert(0) += 1;
+        *path_hits.entry(log.path.as_str()).or_insert(0) += 1;
+        durations.push(log.duration_ms);
+    }
+    durations.sort_unstable();
+
+    let avg_duration_ms = if durations.is_empty() { 0.0 } else { durations.iter().sum::<u64>() as f64 / durations.len() as f64 };
+    // Nearest-rank percentile
+    let p95_duration_ms = if durations.is_empty() { 0 } else { durations[(durations.len() * 95).div_ceil(100) - 1] };
+
+    let mut top_paths: Vec<PathHits> = path_hits.into_iter().map(|(path, hits)| PathHits { path: path.to_string(), hits }).collect();
+    top_paths.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.path.cmp(&b.path)));
+    top_paths.truncate(top.unwrap_or(10));
+
+    Ok(RequestStats {
+        total_requests: logs.len(),
+        status_classes,
+        avg_duration_ms,
+        p95_duration_ms,
+        top_paths,
+        limited_by_log_window: logs.len() >= log_limit,
+        // Logs are newest first
+        window_start: logs.back().map(|log| log.timestamp),
+    })
+}
+
+// The in-memory request logs as a HAR 1.2 document, oldest entry first
+#[tauri::command]
+fn export_logs_har(state: State<'_, AppState>) -> Result<String, String> {
+    let (host, port) = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        let bound = state.started_at.lock().map_err(|e| e.to_string())?.map(|start| start.address.port());
+        (config.host.clone(), bound.unwrap_or(config.port))
+    };
+    let logs = state.logs.lock().map_err(|e| e.to_string())?;
+
+    let har_headers = |headers: &[(String, String)]| -> Vec<serde_json::Value> {
+        headers.iter().map(|(name, value)| serde_json::json!({ "name": name, "value": value })).collect()
+    };
+    let content_type = |headers: &[(String, String)]| {
+        headers.iter().find(|(name, _)| name.eq_ignore_ascii_case("content-type")).map(|(_, v)| v.clone()).unwrap_or_default()
+    };
+    let entries: Vec<serde_json::Value> = logs.iter().rev().map(|log| {
+        let request_body = log.request_body.clone().unwrap_or_default();
+        let response_body = log.response_body.clone().unwrap_or_default();
+        let mut request = serde_json::json!({
+            "method": log.method,
+            "url": format!("http://{}:{}{}", host, port, log.path),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": har_headers(&log.request_headers),
+            "queryString": [],
+            "headersSize": -1,
+            "bodySize": request_body.len(),
+        });
+        if !request_body.is_empty() {
+            request["postData"] = serde_json::json!({ "mimeType": content_type(&log.request_headers), "text": request_body });
+        }
+        serde_json::json!({
+            "startedDateTime": iso8601_millis(log.timestamp),
+            "time": log.duration_ms,
+            "request": request,
+            "response": {
+                "status": log.status_code,
+                "statusText": axum::http::StatusCode::from_u16(log.status_code).ok().and_then(|s| s.canonical_reason()).unwrap_or_default(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": har_headers(&log.response_headers),
+                "content": { "size": response_body.len(), "mimeType": content_type(&log.response_headers), "text": response_body },
+                "redirectURL": "",
+                "headersSize": -1,
+                "bodySize": response_body.len(),
+            },
+            "cache": {},
+            "timings": { "send": 0, "wait": log.duration_ms, "receive": 0 },
+        })
+    }).collect();
+
+    let har = serde_json::json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "evo", "version": env!("CARGO_PKG_VERSION") },
+            "entries": entries,
+        }
+    });
+    serde_json::to_string_pretty(&har).map_err(|e| e.to_string())
+}
+
+// "YYYY-MM-DDTHH:MM:SS.mmmZ" for epoch millis
+fn iso8601_millis(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60, ms % 1000
+    )
+}
+
+#[tauri::command]
+async fn clear_request_logs(app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    state.logs.lock().map_err(|e| e.to_string())?.clear();
+    app_handle.emit("logs-cleared", ()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn query_logs(state: State<'_, AppState>, filter: LogFilter) -> Result<Vec<RequestLog>, String> {
+    let use_log_db = state.config.lock().map_err(|e| e.to_string())?.log_store == "sqlite";
+    let pool = state.log_db.lock().map_err(|e| e.to_string())?.clone();
+    match pool {
+        Some(pool) if use_log_db => server::query_log_store(&pool, &filter).await.map_err(|e| e.to_string()),
+        _ => {
+            let logs = state.logs.lock().map_err(|e| e.to_string())?;
+            Ok(logs
+                .iter()
+                .filter(|log| filter.matches(log))
+                .take(filter.limit.unwrap_or(1000) as usize)
+                .cloned()
+                .collect())
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ServerUptime {
+    uptime_secs: u64,
+    started_at: u64,
+}
+
+// None while the server is stopped
+#[tauri::command]
+fn get_server_uptime(state: State<'_, AppState>) -> Result<Option<ServerUptime>, String> {
+    let started_at = state.started_at.lock().map_err(|e| e.to_string())?;
+    Ok(started_at.map(|start| ServerUptime {
+        uptime_secs: start.instant.elapsed().as_secs(),
+        started_at: start.timestamp,
+    }))
+}
+
+#[derive(serde::Serialize)]
+struct ServerStatus {
+    running: bool,
+    address: Option<String>,
+    // Actual port, also when the configured port is 0
+    port: Option<u16>,
+    uptime_secs: u64,
+}
+
+// What the server is actually doing, unlike ServerConfig.running which is only the intent
+#[tauri::command]
+async fn get_server_status(state: State<'_, AppState>) -> Result<ServerStatus, String> {
+    let started_at = *state.started_at.lock().map_err(|e| e.to_string())?;
+    Ok(ServerStatus {
+        running: started_at.is_some(),
+        address: started_at.map(|start| start.address.to_string()),
+        port: started_at.map(|start| start.address.port()),
+        uptime_secs: started_at.map_or(0, |start| start.instant.elapsed().as_secs()),
+    })
+}
+
+#[tauri::command]
+fn set_toggle(state: State<'_, AppState>, name: String, on: bool) -> Result<(), String> {
+    let mut toggles = state.toggles.lock().map_err(|e| e.to_string())?;
+    toggles.insert(name, on);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_toggles(state: State<'_, AppState>) -> Result<HashMap<String, bool>, String> {
+    let toggles = state.toggles.lock().map_err(|e| e.to_string())?;
+    Ok(toggles.clone())
+}
+
+#[tauri::command]
+fn list_environments(state: State<'_, AppState>) -> Result<HashMap<String, Environment>, String> {
+    let environments = state.environments.lock().map_err(|e| e.to_string())?;
+    Ok(environments.clone())
+}
+
+#[tauri::command]
+fn save_environment(app_handle: AppHandle, state: State<'_, AppState>, name: String, environment: Environment) -> Result<(), String> {
+    let mut environments = state.environments.lock().map_err(|e| e.to_string())?;
+    environments.insert(name, environment);
+    save_environments(&app_handle, &environments)
+}
+
+#[tauri::command]
+fn remove_environment(app_handle: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let mut environments = state.environments.lock().map_err(|e| e.to_string())?;
+    environments.remove(&name);
+    save_environments(&app_handle, &environments)
+}
+
+// Pass None to deactivate; takes effect on the next request
+#[tauri::command]
+fn activate_environment(app_handle: AppHandle, state: State<'_, AppState>, name: Option<String>) -> Result<(), String> {
+    if let Some(name) = &name {
+        let environments = state.environments.lock().map_err(|e| e.to_string())?;
+        if !environments.contains_key(name) {
+            return Err(format!("Environment not found: {}", name));
+        }
+    }
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.active_environment = name;
+        config.clone()
+    };
+    save_server_config(&app_handle, &config)
+}
+
+// Freeze the time seen by mocks at epoch_ms, or pass None to follow the real clock again
+#[tauri::command]
+fn set_time(app_handle: AppHandle, state: State<'_, AppState>, epoch_ms: Option<i64>) -> Result<(), String> {
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.frozen_time = epoch_ms;
+        config.clone()
+    };
+    save_server_config(&app_handle, &config)
+}
+
+// Switch maintenance mode; takes effect on the next request. Turning it on without
+// settings uses the defaults.
+#[tauri::command]
+fn set_maintenance(app_handle: AppHandle, state: State<'_, AppState>, on: bool, maintenance: Option<Maintenance>) -> Result<(), String> {
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.maintenance = if on { Some(maintenance.unwrap_or_default()) } else { None };
+        config.clone()
+    };
+    save_server_config(&app_handle, &config)
+}
+
+// Pause or resume recording for every proxy mock with `record` set
+#[tauri::command]
+fn set_proxy_recording(app_handle: AppHandle, state: State<'_, AppState>, on: bool) -> Result<(), String> {
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.proxy_recording = on;
+        config.clone()
+    };
+    save_server_config(&app_handle, &config)
+}
+
+// Serve only the mocks of `group` (plus ungrouped ones); None serves every group
+#[tauri::command]
+fn set_active_group(app_handle: AppHandle, state: State<'_, AppState>, group: Option<String>) -> Result<(), String> {
+    let config = {
+        let mut config = state.config.lock().map_err(|e| e.to_string())?;
+        config.active_group = group.map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+        config.clone()
+    };
+    save_server_config(&app_handle, &config)
+}
+
+#[tauri::command]
+fn list_groups(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    let mut groups: Vec<String> = mocks.values().filter_map(|m| m.group.clone()).collect();
+    groups.sort();
+    groups.dedup();
+    Ok(groups)
 }
 
-fn save_server_config(app_handle: &AppHandle, config: &ServerConfig) -> Result<(), String> {
-    if let Some(path) = get_server_config_path(app_handle) {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-        let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
+// Explain which mock a request would hit, without touching hit counts or sequences.
+// `path` may carry a query string; headers and body are optional. `port` picks the server
+// instance to match for and defaults to the main server's.
+#[tauri::command]
+fn match_request(state: State<'_, AppState>, method: String, path: String, headers: Option<HashMap<String, String>>, body: Option<String>, port: Option<u16>) -> Result<server::MatchExplanation, String> {
+    let method = method.to_uppercase();
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (path, None),
+    };
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    let mut header_map = axum::http::HeaderMap::new();
+    for (name, value) in headers.unwrap_or_default() {
+        let name = axum::http::HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("Invalid header name '{}'", name))?;
+        let value = axum::http::HeaderValue::from_str(&value).map_err(|_| format!("Invalid value for header '{}'", name))?;
+        header_map.append(name, value);
     }
-    Ok(())
+    let mut state = (*state).clone();
+    state.serving_port = port
+        .or_else(|| state.started_at.lock().unwrap().map(|start| start.address.port()))
+        .unwrap_or_default();
+    let request = server::MatchRequest::new(&state, &method, &path, query.as_deref(), &header_map, body.as_deref().unwrap_or_default());
+    Ok(server::explain_match(&state, &request))
 }
 
-fn load_server_config(app_handle: &AppHandle) -> ServerConfig {
-    if let Some(path) = get_server_config_path(app_handle) {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(config) = serde_json::from_str(&content) {
-                    return config;
-                }
-            }
-        }
-    }
-    ServerConfig::default()
+#[tauri::command]
+fn list_fixtures(state: State<'_, AppState>) -> Result<HashMap<String, RequestFixture>, String> {
+    let fixtures = state.fixtures.lock().map_err(|e| e.to_string())?;
+    Ok(fixtures.clone())
 }
 
 #[tauri::command]
-async fn get_server_config(state: State<'_, AppState>) -> Result<ServerConfig, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    Ok(config.clone())
+fn save_fixture(app_handle: AppHandle, state: State<'_, AppState>, name: String, fixture: RequestFixture) -> Result<(), String> {
+    let mut fixtures = state.fixtures.lock().map_err(|e| e.to_string())?;
+    fixtures.insert(name, fixture);
+    save_fixtures(&app_handle, &fixtures)
 }
 
 #[tauri::command]
-async fn update_server_config(
-    app_handle: AppHandle,
-    state: State<'_, AppState>,
-    config: ServerConfig,
-) -> Result<(), String> {
-    // Update state
-    {
-        let mut state_config = state.config.lock().map_err(|e| e.to_string())?;
-        *state_config = config.clone();
-    }
-    
-    // Save to file
-    save_server_config(&app_handle, &config)?;
-    
-    // Restart server logic handled in frontend or separate command?
-    // Ideally we signal the server thread to restart.
-    // For now, let's just emit an event or rely on the user to restart?
-    // Actually, we can implement a restart mechanism using channels.
-    
-    app_handle.emit("server-config-changed", ()).map_err(|e| e.to_string())?;
-    
-    Ok(())
+fn remove_fixture(app_handle: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let mut fixtures = state.fixtures.lock().map_err(|e| e.to_string())?;
+    fixtures.remove(&name);
+    save_fixtures(&app_handle, &fixtures)
 }
 
-use sqlx::any::AnyPoolOptions;
-use std::time::Duration;
+#[derive(serde::Serialize)]
+struct FixtureResponse {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: String,
+    duration_ms: u64,
+}
 
+// Fire a saved fixture at the running mock server and return what it answered
 #[tauri::command]
-async fn add_db_connection(app_handle: AppHandle, state: State<'_, AppState>, name: String, url: String) -> Result<(), String> {
-    // Validate connection first
-    let _ = AnyPoolOptions::new()
-        .max_connections(1)
-        .acquire_timeout(Duration::from_secs(10))
-        .connect(&url)
-        .await
-        .map_err(|e| format!("Failed to connect: {}", e))?;
+async fn send_fixture(state: State<'_, AppState>, name: String) -> Result<FixtureResponse, String> {
+    let fixture = {
+        let fixtures = state.fixtures.lock().map_err(|e| e.to_string())?;
+        fixtures.get(&name).cloned().ok_or_else(|| format!("Fixture not found: {}", name))?
+    };
+    let Some(start) = *state.started_at.lock().map_err(|e| e.to_string())? else {
+        return Err("Server is not running".to_string());
+    };
+    // The bound port, which differs from the configured one for port 0
+    let (host, port) = {
+        let config = state.config.lock().map_err(|e| e.to_string())?;
+        (config.host.clone(), start.address.port())
+    };
+    // A wildcard bind address isn't something we can connect to
+    let host = if host == "0.0.0.0" || host == "::" { "127.0.0.1".to_string() } else { host };
+    let path = if fixture.path.starts_with('/') { fixture.path } else { format!("/{}", fixture.path) };
+    let url = format!("http://{}:{}{}", host, port, path);
 
-    // If successful, create a lazy pool for storage
-    // Use connect_lazy to be consistent with startup behavior
-    let pool = AnyPoolOptions::new()
-        .max_connections(20)
-        .acquire_timeout(Duration::from_secs(30))
-        .connect_lazy(&url)
-        .map_err(|e| format!("Failed to create pool: {}", e))?;
-    
-    let mut connections = state.db_connections.lock().map_err(|e| e.to_string())?;
-    connections.insert(name.clone(), pool);
-    
-    // Save config
-    // We need to reconstruct the list from current connections + maybe a separate config storage?
-    // Connection pool cannot be serialized. We need to store configs separately.
-    // Let's load existing configs, update, and save.
-    let mut configs = load_db_configs(&app_handle);
-    // Remove existing if same name
-    configs.retain(|c| c.name != name);
-    configs.push(DbConfig { name, url });
-    save_db_configs(&app_handle, &configs)?;
-    
-    Ok(())
+    let method = reqwest::Method::from_bytes(fixture.method.to_uppercase().as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = reqwest::Client::new().request(method, &url);
+    for (k, v) in &fixture.headers {
+        request = request.header(k, v);
+    }
+    if let Some(body) = fixture.body {
+        request = request.body(body);
+    }
+
+    let start = std::time::Instant::now();
+    let res = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+    let status = res.status().as_u16();
+    let headers = res
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
+        .collect();
+    let body = res.text().await.map_err(|e| e.to_string())?;
+
+    Ok(FixtureResponse {
+        status,
+        headers,
+        body,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
 }
 
+// The mocks map as pretty JSON, in the same shape as mocks.json
 #[tauri::command]
-async fn remove_db_connection(app_handle: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
-    let mut connections = state.db_connections.lock().map_err(|e| e.to_string())?;
-    connections.remove(&name);
-    
-    let mut configs = load_db_configs(&app_handle);
-    configs.retain(|c| c.name != name);
-    save_db_configs(&app_handle, &configs)?;
-    
-    Ok(())
+fn export_mocks(state: State<'_, AppState>) -> Result<String, String> {
+    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    serde_json::to_string_pretty(&*mocks).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn get_db_connections(app_handle: AppHandle) -> Result<Vec<DbConfig>, String> {
-    Ok(load_db_configs(&app_handle))
+#[derive(serde::Serialize)]
+struct MockImportSummary {
+    imported: usize,
+    // "<entry>: <reason>" for every entry that was left out
+    skipped: Vec<String>,
 }
 
+// Import mocks from export_mocks output (an array of mocks is accepted too). Invalid
+// entries are skipped and reported; the rest are merged, or replace everything.
 #[tauri::command]
-async fn test_db_connection(url: String) -> Result<String, String> {
-    // Manually install SQLx drivers
-    sqlx::any::install_default_drivers();
-    
-    match AnyPoolOptions::new()
-        .max_connections(1)
-        .acquire_timeout(Duration::from_secs(10))
-        .connect(&url)
-        .await 
-    {
-        Ok(_) => Ok("Connection successful".to_string()),
-        Err(e) => Err(format!("Connection failed: {}", e)),
+fn import_mocks(app_handle: AppHandle, state: State<'_, AppState>, json: String, replace: bool) -> Result<MockImportSummary, String> {
+    let entries: Vec<(String, serde_json::Value)> = match serde_json::from_str(&json).map_err(|e| format!("Invalid JSON: {}", e))? {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        serde_json::Value::Array(items) => items.into_iter().enumerate().map(|(i, v)| (format!("#{}", i), v)).collect(),
+        _ => return Err("Expected an object of mocks or an array".to_string()),
+    };
+
+    let mut imported = HashMap::new();
+    let mut skipped = Vec::new();
+    for (name, value) in entries {
+        let mut mock: MockApi = match serde_json::from_value(value) {
+            Ok(mock) => mock,
+            Err(e) => {
+                skipped.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        mock.method = mock.method.to_uppercase();
+        if !mock.path.starts_with('/') {
+            mock.path = format!("/{}", mock.path);
+        }
+        if let Err(e) = validate_mock(&mock) {
+            skipped.push(format!("{}: {}", name, e));
+            continue;
+        }
+        // Keys are derived from the route, whatever id the file carried
+        mock.id = server::mock_key(&mock.method, &mock.path, mock.host_match.as_deref(), mock.query_match.as_ref(), mock.body_match.as_deref(), mock.header_match.as_ref(), mock.port);
+        imported.insert(mock.id.clone(), mock);
+    }
+
+    if replace && imported.is_empty() && !skipped.is_empty() {
+        return Err(format!("No valid mocks to import; refusing to replace. Skipped: {}", skipped.join("; ")));
+    }
+    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
+    if replace {
+        mocks.clear();
     }
+    let count = imported.len();
+    mocks.extend(imported);
+    save_mocks(&app_handle, &mocks)?;
+    Ok(MockImportSummary { imported: count, skipped })
+}
+
+// Bumped when the bundle layout changes incompatibly
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+struct Bundle {
+    version: u32,
+    mocks: HashMap<String, MockApi>,
+    server_config: ServerConfig,
+    db_connections: Vec<DbConfig>,
+    environments: HashMap<String, Environment>,
+    fixtures: HashMap<String, RequestFixture>,
+    // Set when export_bundle stripped the passwords from the connection URLs
+    redacted_db_urls: bool,
 }
 
+#[derive(serde::Serialize)]
+struct BundleSummary {
+    version: u32,
+    mocks: usize,
+    db_connections: usize,
+    environments: usize,
+    fixtures: usize,
+    // Connections imported from a redacted bundle, whose passwords must be entered again
+    redacted_db_connections: Vec<String>,
+    // False for a dry run
+    applied: bool,
+}
+
+// Everything needed to recreate this setup elsewhere, as the bytes of one JSON document
+// (not a zip archive). With redact_db_urls the passwords are stripped from connection URLs.
 #[tauri::command]
-fn add_mock_api(app_handle: AppHandle, state: State<'_, AppState>, path: String, method: String, response_body: String, status_code: u16, response_type: String) -> Result<(), String> {
-    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
-    let method = method.to_uppercase();
-    // Ensure path starts with /
-    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
-    let key = format!("{} {}", method, path);
-    
-    let mock = MockApi {
-        id: key.clone(),
-        path: path.clone(),
-        method,
-        response_body,
-        status_code,
-        response_type,
+fn export_bundle(app_handle: AppHandle, state: State<'_, AppState>, redact_db_urls: Option<bool>) -> Result<Vec<u8>, String> {
+    let mut db_connections = load_db_configs(&app_handle);
+    let redact_db_urls = redact_db_urls.unwrap_or(false);
+    if redact_db_urls {
+        for config in &mut db_connections {
+            if let Ok(mut url) = url::Url::parse(&config.url) {
+                if url.password().is_some() && url.set_password(None).is_ok() {
+                    config.url = url.to_string();
+                }
+            }
+        }
+    }
+
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        mocks: state.mocks.lock().map_err(|e| e.to_string())?.clone(),
+        server_config: state.config.lock().map_err(|e| e.to_string())?.clone(),
+        db_connections,
+        environments: state.environments.lock().map_err(|e| e.to_string())?.clone(),
+        fixtures: state.fixtures.lock().map_err(|e| e.to_string())?.clone(),
+        redacted_db_urls: redact_db_urls,
     };
-    
-    mocks.insert(key, mock);
-    save_mocks(&app_handle, &mocks)?;
-    Ok(())
+    serde_json::to_vec_pretty(&bundle).map_err(|e| e.to_string())
 }
 
+// Replace the current setup with a bundle (JSON) from export_bundle. Everything is
+// validated before anything is written; with dry_run nothing is applied.
 #[tauri::command]
-fn get_mock_apis(state: State<'_, AppState>) -> Result<Vec<MockApi>, String> {
-    let mocks = state.mocks.lock().map_err(|e| e.to_string())?;
-    Ok(mocks.values().cloned().collect())
+async fn import_bundle(app_handle: AppHandle, state: State<'_, AppState>, bytes: Vec<u8>, dry_run: Option<bool>) -> Result<BundleSummary, String> {
+    let bundle: Bundle = serde_json::from_slice(&bytes).map_err(|e| format!("Invalid bundle: {}", e))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!("Bundle version {} is newer than the supported version {}", bundle.version, BUNDLE_VERSION));
+    }
+    for (id, mock) in &bundle.mocks {
+        if *id != mock.id {
+            return Err(format!("Mock key '{}' doesn't match its id '{}'", id, mock.id));
+        }
+        validate_mock(mock).map_err(|e| format!("{}: {}", id, e))?;
+    }
+    for config in &bundle.db_connections {
+        validate_db_url(config.url.clone()).map_err(|e| format!("DB connection '{}': {}", config.name, e))?;
+    }
+    let proxy_client = server::build_proxy_client(&bundle.server_config)?;
+
+    let summary = BundleSummary {
+        version: bundle.version,
+        mocks: bundle.mocks.len(),
+        db_connections: bundle.db_connections.len(),
+        environments: bundle.environments.len(),
+        fixtures: bundle.fixtures.len(),
+        redacted_db_connections: bundle.db_connections.iter()
+            .filter(|_| bundle.redacted_db_urls)
+            .map(|config| config.name.clone())
+            .collect(),
+        applied: !dry_run.unwrap_or(false),
+    };
+    if !summary.redacted_db_connections.is_empty() {
+        println!("Bundle DB URLs were redacted; re-enter the passwords for: {}", summary.redacted_db_connections.join(", "));
+    }
+    if !summary.applied {
+        return Ok(summary);
+    }
+
+    // Every file is written to its temp path first and only renamed into place once all
+    // of them succeeded, so a failed write leaves the previous setup untouched
+    let data_dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    let staged = [
+        stage_json_file(&app_handle, &data_dir.join(DATA_FILE_NAME), &bundle.mocks),
+        stage_json_file(&app_handle, &data_dir.join(SERVER_CONFIG_FILE_NAME), &bundle.server_config),
+        stage_json_file(&app_handle, &data_dir.join(DB_CONFIG_FILE_NAME), &bundle.db_connections),
+        stage_json_file(&app_handle, &data_dir.join(ENVIRONMENTS_FILE_NAME), &bundle.environments),
+        stage_json_file(&app_handle, &data_dir.join(FIXTURES_FILE_NAME), &bundle.fixtures),
+    ];
+    if let Some(e) = staged.iter().find_map(|file| file.as_ref().err()).cloned() {
+        for file in staged.into_iter().flatten() {
+            let _ = fs::remove_file(file.tmp);
+        }
+        return Err(e);
+    }
+    // If a rename fails, the files already replaced get their previous content back, so
+    // the disk keeps matching the setup still in memory
+    let staged: Vec<StagedFile> = staged.into_iter().flatten().collect();
+    let previous: Vec<Option<Vec<u8>>> = staged.iter().map(|file| fs::read(&file.path).ok()).collect();
+    let mut committed: Vec<PathBuf> = Vec::new();
+    let mut staged = staged.into_iter();
+    while let Some(file) = staged.next() {
+        let path = file.path.clone();
+        if let Err(e) = commit_json_file(file) {
+            for file in staged {
+                let _ = fs::remove_file(file.tmp);
+            }
+            for (path, previous) in committed.into_iter().zip(&previous) {
+                let restored = match previous {
+                    Some(content) => fs::write(&path, content),
+                    None => fs::remove_file(&path),
+                };
+                if let Err(e) = restored {
+                    println!("Failed to restore {}: {}", path.display(), e);
+                }
+            }
+            return Err(e);
+        }
+        committed.push(path);
+    }
+
+    *state.mocks.lock().map_err(|e| e.to_string())? = bundle.mocks;
+    // Hit counts, sequence steps, rotation and compiled patterns belonged to the old mocks
+    state.mock_counters.lock().map_err(|e| e.to_string())?.clear();
+    state.sequence_positions.lock().map_err(|e| e.to_string())?.clear();
+    state.proxy_rotation.lock().map_err(|e| e.to_string())?.clear();
+    state.regex_cache.lock().map_err(|e| e.to_string())?.clear();
+    *state.config.lock().map_err(|e| e.to_string())? = bundle.server_config;
+    *state.proxy_client.lock().map_err(|e| e.to_string())? = proxy_client;
+    *state.environments.lock().map_err(|e| e.to_string())? = bundle.environments;
+    *state.fixtures.lock().map_err(|e| e.to_string())? = bundle.fixtures;
+    {
+        let mut connections = state.db_connections.lock().map_err(|e| e.to_string())?;
+        connections.clear();
+        for config in bundle.db_connections {
+            match lazy_pool(&config.url) {
+                Ok(pool) => {
+                    connections.insert(config.name, pool);
+                }
+                Err(e) => println!("Failed to create lazy pool for DB '{}': {}", config.name, e),
+            }
+        }
+    }
+    ensure_log_store(&app_handle, &state);
+
+    app_handle.emit("server-config-changed", ()).map_err(|e| e.to_string())?;
+    Ok(summary)
 }
 
 #[tauri::command]
-fn remove_mock_api(app_handle: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
-    mocks.remove(&id);
-    save_mocks(&app_handle, &mocks)?;
-    Ok(())
+fn get_config_load_errors(state: State<'_, AppState>) -> Result<Vec<ConfigLoadError>, String> {
+    let errors = state.config_load_errors.lock().map_err(|e| e.to_string())?;
+    Ok(errors.values().cloned().collect())
 }
 
+// Acknowledge a load error so the file may be overwritten by the next save
+// (a copy of the unparseable content remains in the .bak file)
 #[tauri::command]
-fn update_mock_api(app_handle: AppHandle, state: State<'_, AppState>, id: String, path: String, method: String, response_body: String, status_code: u16, response_type: String) -> Result<(), String> {
-    let mut mocks = state.mocks.lock().map_err(|e| e.to_string())?;
-    
-    // If ID (method + path) changed, we need to remove the old one
-    // But since ID is the key, and user might change method/path, 
-    // we effectively do a remove + add, but frontend will pass the 'old' ID.
-    
-    if mocks.contains_key(&id) {
-        mocks.remove(&id);
-    }
-    
-    let method = method.to_uppercase();
-    // Ensure path starts with /
-    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
-    let key = format!("{} {}", method, path);
-    
-    mocks.insert(key.clone(), MockApi {
-        id: key,
-        path,
-        method,
-        response_body,
-        status_code,
-        response_type,
-    });
-    save_mocks(&app_handle, &mocks)?;
+fn resolve_config_load_error(state: State<'_, AppState>, file: String) -> Result<(), String> {
+    let mut errors = state.config_load_errors.lock().map_err(|e| e.to_string())?;
+    errors.remove(&file);
     Ok(())
 }
 
-use tauri::Emitter; // For emit
-use tokio::sync::broadcast;
-
 #[tauri::command]
-async fn get_request_logs(state: State<'_, AppState>) -> Result<Vec<RequestLog>, String> {
-    let logs = state.logs.lock().map_err(|e| e.to_string())?;
-    Ok(logs.iter().cloned().collect())
+fn get_metrics(state: State<'_, AppState>) -> Result<String, String> {
+    let metrics = state.metrics.lock().map_err(|e| e.to_string())?;
+    Ok(metrics.render())
 }
 
 #[tauri::command]
-async fn clear_request_logs(state: State<'_, AppState>) -> Result<(), String> {
-    let mut logs = state.logs.lock().map_err(|e| e.to_string())?;
-    logs.clear();
+fn reset_global_quota(state: State<'_, AppState>) -> Result<(), String> {
+    let mut count = state.request_count.lock().map_err(|e| e.to_string())?;
+    *count = 0;
     Ok(())
 }
 
@@ -301,6 +2014,26 @@ pub fn run() {
         config: config.clone(),
         logs: logs.clone(),
         app_handle: Arc::new(Mutex::new(None)),
+        request_count: Arc::new(Mutex::new(0)),
+        metrics: Arc::new(Mutex::new(Default::default())),
+        log_db: Arc::new(Mutex::new(None)),
+        mock_counters: Arc::new(Mutex::new(HashMap::new())),
+        started_at: Arc::new(Mutex::new(None)),
+        rng: Arc::new(Mutex::new(rand::SeedableRng::from_os_rng())),
+        environments: Arc::new(Mutex::new(HashMap::new())),
+        config_load_errors: Arc::new(Mutex::new(HashMap::new())),
+        proxy_client: Arc::new(Mutex::new(reqwest::Client::new())),
+        toggles: Arc::new(Mutex::new(HashMap::new())),
+        fixtures: Arc::new(Mutex::new(HashMap::new())),
+        proxy_rotation: Arc::new(Mutex::new(HashMap::new())),
+        regex_cache: Arc::new(Mutex::new(HashMap::new())),
+        sequence_positions: Arc::new(Mutex::new(HashMap::new())),
+        kv: Arc::new(Mutex::new(HashMap::new())),
+        running_flag: Arc::new(Mutex::new(false)),
+        server_task: Arc::new(Mutex::new(None)),
+        db_status: Arc::new(Mutex::new(HashMap::new())),
+        servers: Arc::new(Mutex::new(HashMap::new())),
+        serving_port: 0,
     };
     
     // We need to clone app_state to pass to the server task
@@ -314,19 +2047,63 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             add_mock_api, 
             get_mock_apis, 
+            get_mock_api,
+            set_mock_enabled,
+            set_mock_priority,
+            duplicate_mock_api,
+            search_mock_apis,
+            export_mocks,
+            import_mocks,
+            import_openapi,
+            import_postman,
+            export_logs_har,
+            set_proxy_recording,
+            get_server_status,
+            set_active_group,
+            list_groups,
+            match_request,
+            check_db_connections,
+            get_request_stats,
+            start_server_instance,
+            stop_server_instance,
+            list_server_instances,
+            bulk_update_mocks,
             remove_mock_api, 
             update_mock_api,
+            reset_mock_sequence,
             add_db_connection,
             remove_db_connection,
             get_db_connections,
             test_db_connection,
+            validate_db_url,
+            coverage_report,
             get_server_config,
             update_server_config,
             restart_server,
             stop_server,
             start_server_cmd,
             get_request_logs,
-            clear_request_logs
+            clear_request_logs,
+            reset_global_quota,
+            get_metrics,
+            query_logs,
+            get_server_uptime,
+            set_toggle,
+            get_toggles,
+            list_environments,
+            save_environment,
+            remove_environment,
+            activate_environment,
+            set_maintenance,
+            set_time,
+            list_fixtures,
+            save_fixture,
+            remove_fixture,
+            send_fixture,
+            export_bundle,
+            import_bundle,
+            get_config_load_errors,
+            resolve_config_load_error
         ])
         .setup(move |app| {
             // Set app handle in state
@@ -355,6 +2132,35 @@ pub fn run() {
                 let mut state_config = config.lock().unwrap();
                 *state_config = loaded_config.clone();
             }
+            ensure_log_store(app.handle(), &app_state);
+            match server::build_proxy_client(&loaded_config) {
+                Ok(client) => *app_state.proxy_client.lock().unwrap() = client,
+                Err(e) => println!("Failed to configure proxy client: {}", e),
+            }
+
+            // Load environments
+            let loaded_environments = load_environments(app.handle());
+            if let Ok(mut environments) = app_state.environments.lock() {
+                *environments = loaded_environments;
+            }
+
+            // Load request fixtures
+            let loaded_fixtures = load_fixtures(app.handle());
+            if let Ok(mut fixtures) = app_state.fixtures.lock() {
+                *fixtures = loaded_fixtures;
+            }
+
+            // Merge mock definition files from the seed directory
+            if let Some(seed_dir) = get_seed_dir(app.handle(), &loaded_config) {
+                let seeded = load_seed_mocks(&seed_dir);
+                if !seeded.is_empty() {
+                    println!("Seeding {} mocks from {}", seeded.len(), seed_dir.display());
+                    let mut state_mocks = mocks.lock().unwrap();
+                    for mock in seeded {
+                        state_mocks.insert(mock.id.clone(), mock);
+                    }
+                }
+            }
 
             // Load DB connections
             let loaded_configs = load_db_configs(app.handle());
@@ -363,11 +2169,8 @@ pub fn run() {
                 // Connect lazily
                 tauri::async_runtime::spawn(async move {
                     for config in loaded_configs {
-                        // Use connect_lazy to avoid blocking and errors on startup
-                        let pool = AnyPoolOptions::new()
-                            .max_connections(20)
-                            .acquire_timeout(Duration::from_secs(30))
-                            .connect_lazy(&config.url);
+                        // Lazy, to avoid blocking and errors on startup
+                        let pool = lazy_pool(&config.url);
                         
                         if let Ok(pool) = pool {
                                 if let Ok(mut conns) = db_conns.lock() {
@@ -379,13 +2182,16 @@ pub fn run() {
                     }
                 });
             }
+            tauri::async_runtime::spawn(monitor_db_connections(app.handle().clone(), app_state.clone()));
 
             // Start server if configured to run
             if loaded_config.running {
+                *server_state.running_flag.lock().unwrap() = true;
                 let rx = shutdown_tx_clone.subscribe();
-                tauri::async_runtime::spawn(async move {
-                    server::start_server(server_state, rx).await;
-                });
+                let server_task = server_state.server_task.clone();
+                *server_task.lock().unwrap() = Some(tauri::async_runtime::spawn(async move {
+                    server::start_server(server_state, rx, None).await;
+                }));
             }
             Ok(())
         })
@@ -395,31 +2201,104 @@ pub fn run() {
 
 #[tauri::command]
 async fn stop_server(
+    state: State<'_, AppState>,
     shutdown_tx: State<'_, broadcast::Sender<()>>,
 ) -> Result<(), String> {
-    // Signal shutdown
+    // Signal shutdown; returning once it's done lets a following start succeed
+    shutdown_server(&state, &shutdown_tx).await
+}
+
+// Claims the running flag and spawns the server, so two quick starts can't race to bind.
+// Resolves once the listener is bound, or with the bind error.
+async fn spawn_server(state: &AppState, shutdown_tx: &broadcast::Sender<()>) -> Result<(), String> {
+    {
+        let mut running = state.running_flag.lock().map_err(|e| e.to_string())?;
+        if *running {
+            return Err("server already running".to_string());
+        }
+        *running = true;
+    }
+    let rx = shutdown_tx.subscribe();
+    let server_state = state.clone();
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let task = tauri::async_runtime::spawn(async move {
+        server::start_server(server_state, rx, Some(ready_tx)).await;
+    });
+    *state.server_task.lock().map_err(|e| e.to_string())? = Some(task);
+    ready_rx.await.map_err(|_| "server task ended before binding".to_string())?
+}
+
+// Signals shutdown and waits for the running server to let go of its port. The listener
+// closes right away, but a graceful shutdown also waits for open connections, which a
+// stream or websocket can hold forever; after 5 seconds the serve task is aborted instead.
+async fn shutdown_server(state: &AppState, shutdown_tx: &broadcast::Sender<()>) -> Result<(), String> {
     let _ = shutdown_tx.send(());
+    for _ in 0..50 {
+        if !*state.running_flag.lock().map_err(|e| e.to_string())? {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    println!("Server did not stop within 5 seconds, aborting it");
+    if let Some(task) = state.server_task.lock().map_err(|e| e.to_string())?.take() {
+        task.abort();
+    }
+    // The aborted task never reaches its own cleanup
+    *state.started_at.lock().map_err(|e| e.to_string())? = None;
+    *state.running_flag.lock().map_err(|e| e.to_string())? = false;
+    Ok(())
+}
+
+// Extra server instances next to the main one, each serving the mocks bound to its port
+// (MockApi.port) plus the unbound ones
+#[tauri::command]
+async fn start_server_instance(state: State<'_, AppState>, port: u16) -> Result<u16, String> {
+    let address = server::start_server_instance((*state).clone(), port).await?;
+    Ok(address.port())
+}
+
+#[tauri::command]
+async fn stop_server_instance(state: State<'_, AppState>, port: u16) -> Result<(), String> {
+    {
+        let servers = state.servers.lock().map_err(|e| e.to_string())?;
+        let handle = servers.get(&port).ok_or_else(|| format!("No server instance on port {}", port))?;
+        let _ = handle.shutdown.send(());
+    }
+    // Wait for the instance to let go of its port, aborting it after 5 seconds like shutdown_server
+    for _ in 0..50 {
+        if !state.servers.lock().map_err(|e| e.to_string())?.contains_key(&port) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    println!("Server instance on port {} did not stop within 5 seconds, aborting it", port);
+    if let Some(handle) = state.servers.lock().map_err(|e| e.to_string())?.remove(&port) {
+        if let Some(task) = handle.task {
+            task.abort();
+        }
+    }
     Ok(())
 }
 
+#[tauri::command]
+fn list_server_instances(state: State<'_, AppState>) -> Result<Vec<ServerStatus>, String> {
+    let servers = state.servers.lock().map_err(|e| e.to_string())?;
+    let mut instances: Vec<ServerStatus> = servers.values().map(|handle| ServerStatus {
+        running: true,
+        address: Some(handle.start.address.to_string()),
+        port: Some(handle.start.address.port()),
+        uptime_secs: handle.start.instant.elapsed().as_secs(),
+    }).collect();
+    instances.sort_by_key(|s| s.port);
+    Ok(instances)
+}
+
 #[tauri::command]
 async fn start_server_cmd(
     state: State<'_, AppState>,
     shutdown_tx: State<'_, broadcast::Sender<()>>,
 ) -> Result<(), String> {
-    // Check if running already? 
-    // We can assume frontend manages state, or we can use a mutex flag.
-    // For now, let's just ensure we kill any old one first
-    let _ = shutdown_tx.send(());
-    tokio::time::sleep(Duration::from_millis(500)).await;
-
-    let rx = shutdown_tx.subscribe();
-    let server_state = (*state).clone();
-    tauri::async_runtime::spawn(async move {
-        server::start_server(server_state, rx).await;
-    });
-    
-    Ok(())
+    spawn_server(&state, &shutdown_tx).await
 }
 
 #[tauri::command]
@@ -427,22 +2306,58 @@ async fn restart_server(
     state: State<'_, AppState>,
     shutdown_tx: State<'_, broadcast::Sender<()>>,
 ) -> Result<(), String> {
-    // 1. Signal shutdown
-    let _ = shutdown_tx.send(());
+    // 1. Signal shutdown and wait for the port to be released
+    shutdown_server(&state, &shutdown_tx).await?;
     
-    // 2. Wait a bit for port to be released
-    tokio::time::sleep(Duration::from_millis(500)).await;
-    
-    // 3. Check if we should start
+    // 2. Check if we should start
     let config = state.config.lock().map_err(|e| e.to_string())?.clone();
     
     if config.running {
-        let rx = shutdown_tx.subscribe();
-        let server_state = (*state).clone();
-        tauri::async_runtime::spawn(async move {
-            server::start_server(server_state, rx).await;
-        });
+        spawn_server(&state, &shutdown_tx).await?;
     }
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_route_comes_from_the_file_name() {
+        let mock = parse_seed("POST__api__users.json", "{}".to_string()).unwrap();
+        assert_eq!((mock.method.as_str(), mock.path.as_str()), ("POST", "/api/users"));
+        assert_eq!(mock.response_body, "{}");
+        assert_eq!(mock.response_type, "json");
+    }
+
+    #[test]
+    fn seed_header_overrides_the_file_name() {
+        let mock = parse_seed("anything.js", "// delete /users/:id\nreturn {};".to_string()).unwrap();
+        assert_eq!((mock.method.as_str(), mock.path.as_str()), ("DELETE", "/users/:id"));
+        assert_eq!(mock.response_body, "return {};");
+        assert_eq!(mock.response_type, "js");
+    }
+
+    #[test]
+    fn seed_files_without_a_route_are_rejected() {
+        assert!(parse_seed("README.json", "{}".to_string()).is_err());
+        assert!(parse_seed("users.json", "// users list\n{}".to_string()).is_err());
+        assert!(parse_seed("FETCH__users.json", "{}".to_string()).is_err());
+        assert!(parse_seed("GET__users.txt", "{}".to_string()).is_err());
+    }
+
+    #[test]
+    fn postman_paths() {
+        assert_eq!(postman_path(&serde_json::json!("{{baseUrl}}/users/{{id}}?full=1")).as_deref(), Some("/users/:id"));
+        assert_eq!(postman_path(&serde_json::json!("https://api.example.com/v1/items#top")).as_deref(), Some("/v1/items"));
+        assert_eq!(postman_path(&serde_json::json!({ "raw": "x", "path": ["orders", "{{orderId}}"] })).as_deref(), Some("/orders/:orderId"));
+    }
+
+    #[test]
+    fn iso8601_from_epoch_millis() {
+        assert_eq!(iso8601_millis(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601_millis(951_782_400_007), "2000-02-29T00:00:00.007Z");
+        assert_eq!(iso8601_millis(1_700_000_000_123), "2023-11-14T22:13:20.123Z");
+    }
+}
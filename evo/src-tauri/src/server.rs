@@ -1,6 +1,6 @@
 use axum::{
-    extract::State,
-    http::{Method, StatusCode, Uri, HeaderMap},
+    extract::{State, ws::{WebSocket, WebSocketUpgrade, Message as WsMessage}},
+    http::{Method, StatusCode, Uri, HeaderMap, HeaderValue},
     response::{IntoResponse, Response, Json, Html},
     Router,
     body::{Body, to_bytes},
@@ -10,6 +10,7 @@ use std::{collections::{HashMap, VecDeque}, sync::{Arc, Mutex}, time::{SystemTim
 use tower_http::cors::CorsLayer;
 use boa_engine::{Context, Source};
 use sqlx::{Pool, Any};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct MockApi {
@@ -18,7 +19,253 @@ pub struct MockApi {
     pub method: String,
     pub response_body: String,
     pub status_code: u16,
-    pub response_type: String, // "json", "html", "raw", "js"
+    // "ws" proxies the WebSocket to the upstream URL in response_body; "websocket" answers it
+    // here, echoing or replaying the WsScriptMessage script in response_body
+    pub response_type: String, // "json", "html", "raw", "js", "proxy", "static", "ws", "grpc-web", "json-stream", "file", "sequence", "binary", "websocket"
+    #[serde(default = "default_match_type")]
+    pub match_type: String, // "exact", "param" ("/users/:id"), "regex"
+    // Serve only the first hit; afterwards fall through, or answer with exhausted_status if set
+    #[serde(default)]
+    pub single_use: bool,
+    #[serde(default)]
+    pub exhausted_status: Option<u16>,
+    // When non-empty, one of these is picked per request instead of status_code/response_body
+    #[serde(default)]
+    pub weighted_responses: Vec<WeightedResponse>,
+    // Proxy only: Host header sent upstream; "preserve" forwards the client's Host
+    #[serde(default)]
+    pub proxy_host: Option<String>,
+    // Disabled mocks are kept but ignored during matching
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    // Project/namespace the mock belongs to, see ServerConfig.active_group
+    #[serde(default)]
+    pub group: Option<String>,
+    // Shape the client is expected to send; mismatches are logged, not rejected
+    #[serde(default)]
+    pub expected_request: Option<ExpectedRequest>,
+    // Pick the status from a named runtime toggle instead of status_code
+    #[serde(default)]
+    pub toggle_ref: Option<ToggleRef>,
+    // json-stream only: pause between array elements
+    #[serde(default)]
+    pub stream_interval_ms: Option<u64>,
+    // Only serve requests whose Host header (without port) equals this; None serves any host
+    #[serde(default)]
+    pub host_match: Option<String>,
+    // Proxy only: upstream base URLs balanced across instead of response_body
+    #[serde(default)]
+    pub proxy_targets: Vec<String>,
+    // "round-robin" (default) or "random"
+    #[serde(default)]
+    pub proxy_balance: String,
+    // Try the next target when one refuses the connection or times out
+    #[serde(default)]
+    pub proxy_failover: bool,
+    // Query parameters (decoded) that must all be present with these values
+    #[serde(default)]
+    pub query_match: Option<HashMap<String, String>>,
+    // JSON that must be contained in the request body (objects match partially)
+    #[serde(default)]
+    pub body_match: Option<String>,
+    // Request headers that must all be present with these values (names are case-insensitive)
+    #[serde(default)]
+    pub header_match: Option<HashMap<String, String>>,
+    // Expand {{uuid}}, {{timestamp}}, {{randomInt a b}} and {{request.*}} in json/html/raw bodies
+    #[serde(default)]
+    pub templated: bool,
+    // Proxy only: headers set on the upstream response (replacing existing values)
+    #[serde(default)]
+    pub proxy_add_headers: HashMap<String, String>,
+    // Proxy only: upstream response headers dropped before answering (case-insensitive)
+    #[serde(default)]
+    pub proxy_remove_headers: Vec<String>,
+    // Proxy only: regex find/replace applied to the target URL's path before forwarding
+    #[serde(default)]
+    pub proxy_rewrite: Option<(String, String)>,
+    // Wildcard proxy only: save successful upstream responses as mocks for offline replay
+    // (while ServerConfig.proxy_recording is on)
+    #[serde(default)]
+    pub record: bool,
+    // JSON Schema that JSON request bodies must satisfy; failures get a 400 with the errors
+    #[serde(default)]
+    pub request_schema: Option<String>,
+    // Overrides the Content-Type of every response the mock produces; "binary" responses
+    // fall back to application/octet-stream without it
+    #[serde(default)]
+    pub content_type: Option<String>,
+    // Only served by the server instance listening on this port; None serves on all of them
+    #[serde(default)]
+    pub port: Option<u16>,
+    // Access-Control-Allow-Origin for this mock's responses, overriding the server's CORS
+    // settings. "*" mirrors the request's Origin when cors_allow_credentials is set.
+    #[serde(default)]
+    pub cors_origin: Option<String>,
+    // Access-Control-Allow-Credentials for this mock's responses; None keeps the server's
+    #[serde(default)]
+    pub cors_allow_credentials: Option<bool>,
+    // When several mocks match a request, the highest priority wins; ties go to the usual
+    // precedence (exact, param, regex, wildcard, then proxy/static prefixes)
+    #[serde(default)]
+    pub priority: i32,
+}
+
+// Mock ids are "METHOD /path", followed by "?k=v&..." (sorted) for query-matched mocks,
+// " @host" for host-specific mocks and " body:{...}" for body-matched mocks, so the same
+// route can exist once per variant
+pub fn mock_key(method: &str, path: &str, host_match: Option<&str>, query_match: Option<&HashMap<String, String>>, body_match: Option<&str>, header_match: Option<&HashMap<String, String>>, port: Option<u16>) -> String {
+    let mut key = format!("{} {}", method, path);
+    if let Some(query) = query_match.filter(|q| !q.is_empty()) {
+        let mut pairs: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        pairs.sort();
+        key.push('?');
+        key.push_str(&pairs.join("&"));
+    }
+    if let Some(host) = host_match {
+        key.push_str(" @");
+        key.push_str(host);
+    }
+    if let Some(body) = body_match {
+        key.push_str(" body:");
+        key.push_str(body);
+    }
+    if let Some(headers) = header_match.filter(|h| !h.is_empty()) {
+        let mut pairs: Vec<String> = headers.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        pairs.sort();
+        key.push_str(" headers:");
+        key.push_str(&pairs.join("&"));
+    }
+    if let Some(port) = port {
+        key.push_str(&format!(" :{}", port));
+    }
+    key
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToggleRef {
+    // Toggles that were never set count as off
+    pub name: String,
+    pub on_status: u16,
+    pub off_status: u16,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpectedRequest {
+    // Header name -> expected value; an empty value only requires the header to be present
+    pub headers: HashMap<String, String>,
+    // Query parameter -> expected value; an empty value only requires the parameter to be present
+    pub query: HashMap<String, String>,
+    // JSON Schema the request body must satisfy
+    pub body_schema: Option<serde_json::Value>,
+}
+
+impl ExpectedRequest {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.query.is_empty() && self.body_schema.is_none()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockFilter {
+    pub group: Option<String>,
+    pub path_prefix: Option<String>,
+    pub method: Option<String>,
+}
+
+impl MockFilter {
+    pub fn is_empty(&self) -> bool {
+        self.group.is_none() && self.path_prefix.is_none() && self.method.is_none()
+    }
+
+    pub fn matches(&self, mock: &MockApi) -> bool {
+        self.group.as_ref().is_none_or(|g| mock.group.as_ref() == Some(g))
+            && self.path_prefix.as_ref().is_none_or(|p| mock.path.starts_with(p.as_str()))
+            && self.method.as_ref().is_none_or(|m| m.eq_ignore_ascii_case(&mock.method))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WeightedResponse {
+    pub weight: f32,
+    pub status: u16,
+    pub body: String,
+}
+
+// One response of a "sequence" mock, whose response_body is a JSON array of these
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SequenceStep {
+    pub status_code: u16,
+    // A JSON string is served as-is; any other value is serialized
+    #[serde(default)]
+    pub body: serde_json::Value,
+    // "json", "html" or "raw"
+    #[serde(default = "default_step_type")]
+    pub response_type: String,
+}
+
+// One message of a "websocket" mock's scripted replay; response_body is a JSON array of these
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WsScriptMessage {
+    // A JSON string is sent as-is; any other value is serialized. Always a text frame.
+    pub message: serde_json::Value,
+    // Wait before sending, counted from the previous message (or the upgrade)
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+fn default_step_type() -> String {
+    "json".to_string()
+}
+
+fn default_match_type() -> String {
+    "exact".to_string()
+}
+
+impl Default for MockApi {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            path: String::new(),
+            method: "GET".to_string(),
+            response_body: String::new(),
+            status_code: 200,
+            response_type: "json".to_string(),
+            match_type: default_match_type(),
+            single_use: false,
+            exhausted_status: None,
+            weighted_responses: Vec::new(),
+            proxy_host: None,
+            enabled: true,
+            group: None,
+            expected_request: None,
+            toggle_ref: None,
+            stream_interval_ms: None,
+            host_match: None,
+            proxy_targets: Vec::new(),
+            proxy_balance: String::new(),
+            proxy_failover: false,
+            query_match: None,
+            header_match: None,
+            body_match: None,
+            templated: false,
+            proxy_add_headers: HashMap::new(),
+            proxy_remove_headers: Vec::new(),
+            proxy_rewrite: None,
+            record: false,
+            request_schema: None,
+            content_type: None,
+            port: None,
+            cors_origin: None,
+            cors_allow_credentials: None,
+            priority: 0,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -31,6 +278,97 @@ pub struct RequestLog {
     pub timestamp: u64,
     pub request_body: Option<String>,
     pub response_body: Option<String>,
+    // Index into the mock's weighted_responses that was served
+    #[serde(default)]
+    pub variant: Option<usize>,
+    // Differences between the request and the mock's expected_request
+    #[serde(default)]
+    pub contract_violations: Vec<String>,
+    // Delay requested by the client through X-Mock-Delay
+    #[serde(default)]
+    pub header_delay_ms: Option<u64>,
+    // Upstream URL a proxied request was forwarded to
+    #[serde(default)]
+    pub proxy_target: Option<String>,
+    // Authorization values are masked unless ServerConfig.log_sensitive_headers is set
+    #[serde(default)]
+    pub request_headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub response_headers: Vec<(String, String)>,
+}
+
+// Response extension carrying the served weighted variant from process_request to the logger
+#[derive(Clone, Copy)]
+struct ServedVariant(usize);
+
+// Response extension marking a streamed body that the logger must not buffer
+#[derive(Clone, Copy)]
+struct StreamedBody;
+
+// Which rule served a request, filled in by process_request for the debug headers
+// and the request log
+#[derive(Clone, Debug, Default)]
+struct MatchedRoute {
+    id: String,
+    response_type: String,
+    proxy_target: Option<String>,
+    contract_violations: Vec<String>,
+    // The mock's content_type, applied to whatever response it produced
+    content_type: Option<String>,
+    // The mock's CORS overrides, likewise applied to whatever response it produced
+    cors: Option<MockCors>,
+}
+
+// CORS headers of a mock with cors_origin or cors_allow_credentials. Applied in handler and
+// again outside the CORS layer, which would otherwise overwrite them.
+#[derive(Clone, Debug, Default)]
+struct MockCors {
+    origin: Option<HeaderValue>,
+    allow_credentials: Option<bool>,
+}
+
+impl MockCors {
+    fn for_mock(mock: &MockApi, request_headers: &HeaderMap) -> Option<MockCors> {
+        if mock.cors_origin.is_none() && mock.cors_allow_credentials.is_none() {
+            return None;
+        }
+        // Browsers reject "*" on credentialed responses, so the caller's Origin is echoed back
+        let mirror = mock.cors_allow_credentials == Some(true) && mock.cors_origin.as_deref().is_none_or(|o| o.trim() == "*");
+        let origin = if mirror {
+            request_headers.get(axum::http::header::ORIGIN).cloned()
+        } else {
+            mock.cors_origin.as_deref().and_then(|o| HeaderValue::from_str(o.trim()).ok())
+        };
+        Some(MockCors { origin, allow_credentials: mock.cors_allow_credentials })
+    }
+
+    fn apply(&self, headers: &mut HeaderMap) {
+        use axum::http::header;
+        if let Some(origin) = &self.origin {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            if origin != "*" {
+                headers.append(header::VARY, HeaderValue::from_static("origin"));
+            }
+        }
+        match self.allow_credentials {
+            Some(true) => {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+            }
+            Some(false) => {
+                headers.remove(header::ACCESS_CONTROL_ALLOW_CREDENTIALS);
+            }
+            None => {}
+        }
+    }
+}
+
+// Re-applies a mock's CORS overrides on top of the CORS layer's headers
+async fn mock_cors(request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let mut response = next.run(request).await;
+    if let Some(cors) = response.extensions().get::<MockCors>().cloned() {
+        cors.apply(response.headers_mut());
+    }
+    response
 }
 
 #[derive(Clone)]
@@ -40,17 +378,314 @@ pub struct AppState {
     // Key: Connection ID (name)
     pub db_connections: Arc<Mutex<HashMap<String, Pool<Any>>>>,
     pub config: Arc<Mutex<ServerConfig>>,
-    // Request Logs (Max 100)
+    // Request Logs (newest first, at most ServerConfig.log_limit)
     pub logs: Arc<Mutex<VecDeque<RequestLog>>>,
     // App handle for emitting events
     pub app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    // Total requests served since startup (or the last quota reset)
+    pub request_count: Arc<Mutex<u64>>,
+    // Request latency histogram exposed via get_metrics and /__metrics
+    pub metrics: Arc<Mutex<LatencyHistogram>>,
+    // SQLite log store, opened when ServerConfig.log_store is "sqlite"
+    pub log_db: Arc<Mutex<Option<sqlx::SqlitePool>>>,
+    // Hits per mock id since startup or the last reset_mock_sequence
+    pub mock_counters: Arc<Mutex<HashMap<String, usize>>>,
+    // When the current server instance bound its listener; None while stopped
+    pub started_at: Arc<Mutex<Option<ServerStart>>>,
+    // Shared RNG, reseeded from ServerConfig.random_seed on every server start
+    pub rng: Arc<Mutex<StdRng>>,
+    // Key: environment name
+    pub environments: Arc<Mutex<HashMap<String, Environment>>>,
+    // Key: file name of a config file that failed to parse at startup
+    pub config_load_errors: Arc<Mutex<HashMap<String, ConfigLoadError>>>,
+    // HTTP client shared by all proxy requests, rebuilt when the proxy TLS settings change
+    pub proxy_client: Arc<Mutex<reqwest::Client>>,
+    // Named on/off switches referenced by MockApi.toggle_ref; not persisted
+    pub toggles: Arc<Mutex<HashMap<String, bool>>>,
+    // Key: fixture name
+    pub fixtures: Arc<Mutex<HashMap<String, RequestFixture>>>,
+    // Key: mock id; next round-robin position in the mock's proxy_targets
+    pub proxy_rotation: Arc<Mutex<HashMap<String, usize>>>,
+    // Compiled regex mock paths, keyed by pattern, and proxy_rewrite patterns, keyed by
+    // "rewrite:" + pattern (mock paths are compiled anchored, so they can't share an entry)
+    pub regex_cache: Arc<Mutex<HashMap<String, regex::Regex>>>,
+    // Key: mock id; index of the next step served by a "sequence" mock
+    pub sequence_positions: Arc<Mutex<HashMap<String, usize>>>,
+    // Shared store behind kv.get/set/delete in JS mocks; lives as long as the process
+    pub kv: Arc<Mutex<HashMap<String, String>>>,
+    // Set when a server start is claimed, cleared once it failed to bind or has shut down
+    pub running_flag: Arc<Mutex<bool>>,
+    // The task running the main server, aborted when a shutdown doesn't finish in time
+    pub server_task: Arc<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>>,
+    // Key: DB connection name; whether its last health check succeeded
+    pub db_status: Arc<Mutex<HashMap<String, bool>>>,
+    // Extra server instances started with start_server_instance, keyed by bound port
+    pub servers: Arc<Mutex<HashMap<u16, ServerHandle>>>,
+    // Port of the server instance a clone of the state serves requests for; 0 elsewhere
+    pub serving_port: u16,
+}
+
+// A running extra server instance
+pub struct ServerHandle {
+    pub shutdown: broadcast::Sender<()>,
+    pub start: ServerStart,
+    // The instance's serve task, aborted when a shutdown doesn't finish in time
+    pub task: Option<tokio::task::AbortHandle>,
+}
+
+pub fn build_proxy_client(config: &ServerConfig) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(path) = &config.proxy_ca_cert {
+        let pem = std::fs::read(path).map_err(|e| format!("Failed to read CA cert {}: {}", path, e))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| format!("Invalid CA cert {}: {}", path, e))?;
+        builder = builder.tls_certs_merge(certs);
+    }
+    if config.proxy_accept_invalid_certs {
+        println!("[PROXY] TLS certificate verification is disabled for proxy targets");
+        builder = builder.tls_danger_accept_invalid_certs(true);
+    }
+    if config.proxy_timeout_ms > 0 {
+        builder = builder.timeout(std::time::Duration::from_millis(config.proxy_timeout_ms));
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigLoadError {
+    pub file: String,
+    pub error: String,
+    // Where the unparseable file was copied to
+    pub backup: Option<String>,
+    // The backup its data was loaded from instead, when that parsed; saves aren't blocked then
+    pub recovered_from: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Environment {
+    // Substituted for {{var.NAME}} in response bodies and proxy targets
+    pub variables: HashMap<String, String>,
+    // Relative proxy targets (starting with "/") are resolved against this
+    pub proxy_base_url: Option<String>,
+}
+
+// A saved request that send_fixture fires at the running server
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestFixture {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ServerStart {
+    pub instant: Instant,
+    // Wall-clock start time in epoch millis
+    pub timestamp: u64,
+    // Address the listener actually bound to
+    pub address: std::net::SocketAddr,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LatencyHistogram {
+    // Upper bounds in milliseconds, sorted ascending
+    pub bounds: Vec<f64>,
+    // Per-bucket (non-cumulative) counts, same length as `bounds`
+    pub counts: Vec<u64>,
+    pub sum_ms: f64,
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, bounds: &[f64], value_ms: f64) {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        bounds.dedup();
+        // Boundaries changed in the config: start over rather than mixing bucket layouts
+        if bounds != self.bounds {
+            *self = LatencyHistogram {
+                counts: vec![0; bounds.len()],
+                bounds,
+                ..Default::default()
+            };
+        }
+
+        if let Some(i) = self.bounds.iter().position(|b| value_ms <= *b) {
+            self.counts[i] += 1;
+        }
+        self.sum_ms += value_ms;
+        self.count += 1;
+    }
+
+    // Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let name = "evo_request_duration_milliseconds";
+        let mut out = format!(
+            "# HELP {name} Mock server request latency in milliseconds.\n# TYPE {name} histogram\n"
+        );
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            out.push_str(&format!("{name}_bucket{{le=\"{}\"}} {}\n", bound, cumulative));
+        }
+        out.push_str(&format!("{name}_bucket{{le=\"+Inf\"}} {}\n", self.count));
+        out.push_str(&format!("{name}_sum {}\n", self.sum_ms));
+        out.push_str(&format!("{name}_count {}\n", self.count));
+        out
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String, // "0.0.0.0" or "127.0.0.1"
     pub running: bool,
+    // Directory of mock definition files loaded at startup (defaults to "<app data>/mocks.d")
+    pub mocks_dir: Option<String>,
+    // Cumulative request quota across the whole server
+    pub global_quota: Option<GlobalQuota>,
+    // Latency histogram bucket upper bounds in milliseconds
+    pub metrics_buckets: Vec<f64>,
+    // Header names (case-insensitive) exposed to JS as request.headers; None exposes all
+    pub js_header_allowlist: Option<Vec<String>>,
+    // Longest a js mock may run, timers included, before the request gets a 504; 0 disables.
+    // Also bounds the pre/post hook scripts, which are skipped when they run over.
+    pub js_timeout_ms: u64,
+    // "memory" (default) or "sqlite" to additionally persist every log for query_logs
+    pub log_store: String,
+    // Fixed seed for reproducible random choices; None seeds from the OS
+    pub random_seed: Option<u64>,
+    // Name of the environment whose variables and proxy base URL are applied
+    pub active_environment: Option<String>,
+    // Add X-Mock-Matched-Id / X-Mock-Response-Type / X-Mock-Proxy-Target to every response.
+    // Off by default since it exposes mock internals to clients.
+    pub debug_headers: bool,
+    // PEM file with extra CA certificate(s) trusted for HTTPS proxy targets
+    pub proxy_ca_cert: Option<String>,
+    // Skip certificate verification for proxy targets. Development only: this also accepts
+    // expired, self-signed and wrong-host certificates, so any upstream can be impersonated.
+    pub proxy_accept_invalid_certs: bool,
+    // Serve an HTML list of the registered mocks at GET / unless a mock handles it
+    pub show_index: bool,
+    // Delay responses by the milliseconds in a request's X-Mock-Delay header (capped)
+    pub honor_delay_header: bool,
+    // When set, every request gets this response instead of its mock
+    pub maintenance: Option<Maintenance>,
+    // Returned instead of the plain 413 when a request body exceeds max_body_bytes. Requests
+    // to proxy mocks are read through the same limit, so they get it too; responses coming
+    // back from a proxy target aren't size-limited, so there's no proxy-side check.
+    pub max_body_response: Option<CustomResponse>,
+    // Epoch millis returned by {{now}} in templated mocks and by JS Date.now()/new Date()
+    // instead of the real clock. Only mock-generated values are affected; logs and the OS
+    // clock are not.
+    pub frozen_time: Option<i64>,
+    // Number of request logs kept in memory (capped at MAX_LOG_LIMIT)
+    pub log_limit: usize,
+    // Require HTTP Basic credentials on every request; disabled when both are empty
+    pub basic_auth_user: Option<String>,
+    pub basic_auth_pass: Option<String>,
+    // Origins allowed by CORS; empty keeps the permissive policy
+    pub cors_origins: Vec<String>,
+    // Methods allowed by CORS when cors_origins is set; empty allows the common verbs
+    pub cors_methods: Vec<String>,
+    // Send Access-Control-Allow-Credentials (only applies with explicit cors_origins)
+    pub cors_allow_credentials: bool,
+    // Log Authorization header values as-is instead of "***"
+    pub log_sensitive_headers: bool,
+    // Largest accepted request body; larger ones get 413 (or max_body_response). 0 = unlimited
+    pub max_body_bytes: usize,
+    // Timeout for each upstream request made by the proxy (and JS fetch). 0 = none
+    pub proxy_timeout_ms: u64,
+    // Extra attempts per proxy target for idempotent methods that fail to connect
+    pub proxy_retries: u32,
+    // Global switch for proxy mocks with `record`; turn off to freeze the recorded set
+    pub proxy_recording: bool,
+    // Chaos mode: each request is delayed by a random 0..=chaos_delay_ms_max, or fails with
+    // a 500 at chaos_failure_rate (0.0-1.0). Both 0 disables it.
+    pub chaos_delay_ms_max: u64,
+    pub chaos_failure_rate: f32,
+    // Always answers 200 with the uptime, ahead of maintenance, chaos and mocks. Empty disables
+    pub health_path: String,
+    // Only mocks in this group (plus ungrouped ones) are served. None serves every group
+    pub active_group: Option<String>,
+    // Answer for requests no mock matches, instead of the built-in 404. An empty type keeps
+    // the 404; "proxy" forwards to default_response_body as the target base URL
+    pub default_response_type: String,
+    pub default_status_code: u16,
+    pub default_response_body: String,
+    // Answer OPTIONS requests with a 204 and the CORS headers unless an OPTIONS mock covers
+    // the path
+    pub auto_preflight: bool,
+    // JS run on every request before matching; may rewrite request.path, request.query,
+    // request.headers and request.body, and add response headers with response.setHeader
+    pub pre_request_script: Option<String>,
+    // JS run on every response; sees request and response.status/headers and can call
+    // response.setHeader and response.setStatusCode
+    pub post_response_script: Option<String>,
+}
+
+// Upper bound for log_limit so a typo can't grow the in-memory log without bound
+pub const MAX_LOG_LIMIT: usize = 100_000;
+
+// Upper bound for X-Mock-Delay so a client can't tie up the server indefinitely
+const MAX_HEADER_DELAY_MS: u64 = 30_000;
+
+// Upper bound for a single setTimeout delay in JS mocks
+const MAX_JS_TIMER_MS: u64 = 30_000;
+
+// Upper bound for one WsScriptMessage.delay_ms, checked when the mock is stored
+pub const MAX_WS_SCRIPT_DELAY_MS: u64 = 60_000;
+
+// Loop iterations allowed per millisecond of js_timeout_ms. The timed-out request is answered
+// right away, but boa can't be interrupted from outside, so this is what eventually stops a
+// runaway loop and frees its blocking thread.
+const JS_LOOP_ITERATIONS_PER_MS: u64 = 100_000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Maintenance {
+    pub status: u16,
+    pub body: String,
+    // "json", "html" or "raw"
+    pub response_type: String,
+    // Paths still served normally; a trailing '*' matches by prefix
+    pub allow_paths: Vec<String>,
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self {
+            status: 503,
+            body: "<h1>Down for maintenance</h1>".to_string(),
+            response_type: "html".to_string(),
+            allow_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CustomResponse {
+    pub status: u16,
+    pub body: String,
+    // "json", "html" or "raw"
+    #[serde(default)]
+    pub response_type: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GlobalQuota {
+    pub limit: u64,
+    pub status: u16,
+    pub body: String,
+    #[serde(default = "default_retry_after_secs")]
+    pub retry_after_secs: u64,
+}
+
+fn default_retry_after_secs() -> u64 {
+    60
 }
 
 impl Default for ServerConfig {
@@ -59,16 +694,59 @@ impl Default for ServerConfig {
             port: 3000,
             host: "127.0.0.1".to_string(),
             running: true,
+            mocks_dir: None,
+            global_quota: None,
+            metrics_buckets: vec![5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0],
+            js_header_allowlist: None,
+            js_timeout_ms: 5000,
+            log_store: "memory".to_string(),
+            random_seed: None,
+            active_environment: None,
+            debug_headers: false,
+            proxy_ca_cert: None,
+            proxy_accept_invalid_certs: false,
+            show_index: false,
+            honor_delay_header: false,
+            maintenance: None,
+            max_body_response: None,
+            frozen_time: None,
+            log_limit: 100,
+            basic_auth_user: None,
+            basic_auth_pass: None,
+            cors_origins: Vec::new(),
+            cors_methods: Vec::new(),
+            cors_allow_credentials: false,
+            log_sensitive_headers: false,
+            max_body_bytes: 10 * 1024 * 1024,
+            proxy_timeout_ms: 30_000,
+            proxy_retries: 0,
+            proxy_recording: true,
+            chaos_delay_ms_max: 0,
+            chaos_failure_rate: 0.0,
+            health_path: "/__health".to_string(),
+            active_group: None,
+            default_response_type: String::new(),
+            default_status_code: 404,
+            default_response_body: String::new(),
+            auto_preflight: true,
+            pre_request_script: None,
+            post_response_script: None,
         }
     }
 }
 
 use tokio::sync::broadcast;
 
-pub async fn start_server(state: AppState, mut shutdown_rx: broadcast::Receiver<()>) {
-    let (config_port, config_host) = {
+// `ready` receives the bind result, so a caller can report a taken port
+pub async fn start_server(state: AppState, mut shutdown_rx: broadcast::Receiver<()>, ready: Option<tokio::sync::oneshot::Sender<Result<(), String>>>) {
+    let (config_port, config_host, random_seed, cors, max_body_bytes) = {
         let config = state.config.lock().unwrap();
-        (config.port, config.host.clone())
+        (config.port, config.host.clone(), config.random_seed, build_cors_layer(&config), config.max_body_bytes)
+    };
+
+    *state.rng.lock().unwrap() = match random_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
     };
 
     let addr = format!("{}:{}", config_host, config_port);
@@ -76,16 +754,45 @@ pub async fn start_server(state: AppState, mut shutdown_rx: broadcast::Receiver<
         Ok(l) => l,
         Err(e) => {
             println!("Failed to bind to {}: {}", addr, e);
+            *state.running_flag.lock().unwrap() = false;
+            let error = format!("Failed to bind to {}: {}", addr, e);
+            if let Some(app_handle) = state.app_handle.lock().unwrap().clone() {
+                use tauri::Emitter;
+                let _ = app_handle.emit("server-start-failed", &error);
+            }
+            if let Some(ready) = ready {
+                let _ = ready.send(Err(error));
+            }
             return;
         }
     };
 
-    println!("Server listening on {}", listener.local_addr().unwrap());
+    let local_addr = listener.local_addr().unwrap();
+    println!("Server listening on {}", local_addr);
 
-    let app = Router::new()
-        .fallback(handler)
-        .layer(CorsLayer::permissive())
-        .with_state(state);
+    let start = ServerStart {
+        instant: Instant::now(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+        address: local_addr,
+    };
+    *state.started_at.lock().unwrap() = Some(start);
+    // With port 0 this is the only way to learn the port the OS picked
+    if let Some(app_handle) = state.app_handle.lock().unwrap().clone() {
+        use tauri::Emitter;
+        let _ = app_handle.emit("server-started", serde_json::json!({
+            "address": local_addr.to_string(),
+            "port": local_addr.port(),
+        }));
+    }
+    if let Some(ready) = ready {
+        let _ = ready.send(Ok(()));
+    }
+    let started_at = state.started_at.clone();
+    let running_flag = state.running_flag.clone();
+
+    let mut state = state;
+    state.serving_port = local_addr.port();
+    let app = build_router(state, cors, max_body_bytes);
 
     axum::serve(listener, app)
         .with_graceful_shutdown(async move {
@@ -94,123 +801,842 @@ pub async fn start_server(state: AppState, mut shutdown_rx: broadcast::Receiver<
         })
         .await
         .unwrap();
+
+    // Only clear our own entry; a restarted instance may already have replaced it
+    let mut current = started_at.lock().unwrap();
+    if current.is_some_and(|s| s.instant == start.instant) {
+        *current = None;
+    }
+    *running_flag.lock().unwrap() = false;
 }
 
-#[axum::debug_handler]
-async fn handler(
-    State(state): State<AppState>,
-    method: Method,
-    uri: Uri,
-    headers: HeaderMap,
-    body: String,
-) -> Response {
-    let start_time = Instant::now();
-    let request_body_clone = body.clone();
-    
-    // Process request
-    let response = process_request(state.clone(), method.clone(), uri.clone(), headers, body).await;
-    
-    // Log request
-    let duration = start_time.elapsed().as_millis() as u64;
-    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
-    let status_code = response.status().as_u16();
-    
-    // Try to capture response body for logging
-    // Note: This consumes the response body, so we need to reconstruct it.
-    // For now, let's just log "Response body logged" placeholder or try to peek if possible.
-    // Actually, we can read the bytes, store them, and create a new body.
-    
-    let (parts, body) = response.into_parts();
-    let bytes = to_bytes(body, usize::MAX).await.unwrap_or_default();
-    let response_body_str = String::from_utf8(bytes.to_vec()).ok();
-    
-    let log = RequestLog {
-        id: uuid::Uuid::new_v4().to_string(),
-        method: method.to_string(),
-        path: uri.path().to_string(),
-        status_code,
-        duration_ms: duration,
-        timestamp,
-        request_body: Some(request_body_clone),
-        response_body: response_body_str.clone(),
+// The app served by the main server and by every extra instance
+fn build_router(state: AppState, cors: CorsLayer, max_body_bytes: usize) -> Router {
+    Router::new()
+        .route("/__metrics", axum::routing::get(metrics_handler))
+        .fallback(handler)
+        // RequestBodyLimitLayer is the only body limit, replacing axum's 2MB default
+        .layer(axum::extract::DefaultBodyLimit::disable())
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(if max_body_bytes == 0 { usize::MAX } else { max_body_bytes }))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), declared_body_limit))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), basic_auth))
+        .layer(cors)
+        .layer(axum::middleware::from_fn(mock_cors))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auto_preflight))
+        .with_state(state)
+}
+
+// Start an extra server instance on `port` (0 picks a free one) next to the main server,
+// with its own shutdown channel. It serves the unbound mocks plus those bound to its port.
+// Returns the bound address.
+pub async fn start_server_instance(state: AppState, port: u16) -> Result<std::net::SocketAddr, String> {
+    if port != 0 && state.servers.lock().unwrap().contains_key(&port) {
+        return Err(format!("A server instance is already running on port {}", port));
+    }
+    let (host, cors, max_body_bytes) = {
+        let config = state.config.lock().unwrap();
+        (config.host.clone(), build_cors_layer(&config), config.max_body_bytes)
     };
-    
-    // Store log
-    if let Ok(mut logs) = state.logs.lock() {
-        logs.push_front(log.clone());
-        if logs.len() > 100 {
-            logs.pop_back();
+    let addr = format!("{}:{}", host, port);
+    let listener = tokio::net::TcpListener::bind(&addr).await.map_err(|e| format!("Failed to bind to {}: {}", addr, e))?;
+    let local_addr = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("Server instance listening on {}", local_addr);
+
+    let (shutdown_tx, mut shutdown_rx) = broadcast::channel(1);
+    let start = ServerStart {
+        instant: Instant::now(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+        address: local_addr,
+    };
+    state.servers.lock().unwrap().insert(local_addr.port(), ServerHandle { shutdown: shutdown_tx, start, task: None });
+
+    let servers = state.servers.clone();
+    let mut instance_state = state;
+    instance_state.serving_port = local_addr.port();
+    let app = build_router(instance_state, cors, max_body_bytes);
+    let servers_for_task = servers.clone();
+    let task = tokio::spawn(async move {
+        let result = axum::serve(listener, app)
+            .with_graceful_shutdown(async move {
+                shutdown_rx.recv().await.ok();
+                println!("Server instance on {} shutting down...", local_addr);
+            })
+            .await;
+        if let Err(e) = result {
+            println!("Server instance on {} failed: {}", local_addr, e);
         }
+        // Only remove our own entry; a restarted instance may already have replaced it
+        let mut servers = servers_for_task.lock().unwrap();
+        if servers.get(&local_addr.port()).is_some_and(|s| s.start.instant == start.instant) {
+            servers.remove(&local_addr.port());
+        }
+    });
+    if let Some(handle) = servers.lock().unwrap().get_mut(&local_addr.port()).filter(|s| s.start.instant == start.instant) {
+        handle.task = Some(task.abort_handle());
     }
-    
-    // Emit event
-    if let Ok(handle_guard) = state.app_handle.lock() {
-        if let Some(app_handle) = handle_guard.as_ref() {
-             use tauri::Emitter;
-             if let Err(e) = app_handle.emit("new-request-log", log.clone()) {
-                 println!("Failed to emit log: {}", e);
-             }
+    Ok(local_addr)
+}
+
+fn build_cors_layer(config: &ServerConfig) -> CorsLayer {
+    if config.cors_origins.is_empty() {
+        return CorsLayer::permissive();
+    }
+
+    let origins: Vec<HeaderValue> = config.cors_origins.iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin.trim()) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                println!("Ignoring invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect();
+
+    // Wildcards aren't allowed together with credentials, so mirror the request headers instead
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(cors_methods(config))
+        .allow_headers(tower_http::cors::AllowHeaders::mirror_request())
+        .allow_credentials(config.cors_allow_credentials)
+}
+
+// Methods allowed by CORS when cors_origins is set; empty cors_methods allows the common verbs
+fn cors_methods(config: &ServerConfig) -> Vec<Method> {
+    if config.cors_methods.is_empty() {
+        vec![Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::HEAD, Method::OPTIONS]
+    } else {
+        config.cors_methods.iter()
+            .filter_map(|m| Method::from_bytes(m.trim().to_uppercase().as_bytes()).ok())
+            .collect()
+    }
+}
+
+// Answers OPTIONS requests with a 204 and CORS headers matching build_cors_layer, unless an
+// enabled OPTIONS mock covers the path. Runs outside the CORS layer, which would otherwise
+// answer preflights itself before the mock is reached.
+async fn auto_preflight(State(state): State<AppState>, mut request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    if request.method() != Method::OPTIONS {
+        return next.run(request).await;
+    }
+    let config = state.config.lock().unwrap().clone();
+    if !config.auto_preflight {
+        return next.run(request).await;
+    }
+
+    let explicit_mock = {
+        let path = request.uri().path();
+        let match_request = MatchRequest::new(&state, "OPTIONS", path, request.uri().query(), request.headers(), "");
+        let mocks = state.mocks.lock().unwrap();
+        mocks.values().any(|mock| mock.method == "OPTIONS" && match_request.route_mismatch(mock).is_none() && mock_path_fits(&state, mock, path))
+    };
+    if explicit_mock {
+        // Without the preflight marker the CORS layer passes the request on to the mock,
+        // still adding Access-Control-Allow-Origin to its response
+        request.headers_mut().remove(axum::http::header::ACCESS_CONTROL_REQUEST_METHOD);
+        return next.run(request).await;
+    }
+
+    use axum::http::header;
+    // CORS overrides of the mock that will serve the actual request
+    let mock_cors = request.headers().get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|m| m.to_str().ok())
+        .and_then(|requested| {
+            let path = request.uri().path();
+            let requested = requested.to_uppercase();
+            let match_request = MatchRequest::new(&state, &requested, path, request.uri().query(), request.headers(), "");
+            select_mock(&state, &match_request)
+                .map(|(mock, _, _)| mock)
+                .or_else(|| select_prefix_mock(&state, &match_request).map(|(mock, _)| mock))
+        })
+        .and_then(|mock| MockCors::for_mock(&mock, request.headers()));
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    let request_headers = request.headers();
+    let headers = response.headers_mut();
+    let origin = request_headers.get(header::ORIGIN);
+    if config.cors_origins.is_empty() {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("*"));
+    } else {
+        let allowed = origin.and_then(|o| o.to_str().ok()).is_some_and(|o| config.cors_origins.iter().any(|allowed| allowed.trim() == o));
+        if let (true, Some(origin)) = (allowed, origin) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            if config.cors_allow_credentials {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+            }
+        }
+        let methods = cors_methods(&config).iter().map(|m| m.as_str()).collect::<Vec<_>>().join(", ");
+        if let Ok(methods) = HeaderValue::from_str(&methods) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
         }
     }
-    
-    // Reconstruct response
-    let response = Response::from_parts(parts, Body::from(bytes));
+    match request_headers.get(header::ACCESS_CONTROL_REQUEST_HEADERS) {
+        Some(requested) => {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, requested.clone());
+        }
+        None if config.cors_origins.is_empty() => {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("*"));
+        }
+        None => {}
+    }
+    headers.insert(header::VARY, HeaderValue::from_static("origin, access-control-request-method, access-control-request-headers"));
+    if let Some(mock_cors) = mock_cors {
+        mock_cors.apply(headers);
+    }
     response
 }
 
-async fn process_request(
-    state: AppState,
-    method: Method,
-    uri: Uri,
-    headers: HeaderMap,
-    body: String,
-) -> Response {
-    let path = uri.path();
-    let key = format!("{} {}", method, path);
-    
-    // Check exact match first
-    let mock_opt = {
-        let mocks = state.mocks.lock().unwrap();
-        // Try specific method first
-        if let Some(mock) = mocks.get(&key) {
-            Some(mock.clone())
+// RequestBodyLimitLayer answers an oversized Content-Length itself, before handler can
+// substitute max_body_response, so that case is caught here first
+async fn declared_body_limit(State(state): State<AppState>, request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let (max_body_bytes, custom) = {
+        let config = state.config.lock().unwrap();
+        (config.max_body_bytes, config.max_body_response.clone())
+    };
+    let declared = request.headers()
+        .get(axum::http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let (Some(custom), Some(declared)) = (custom, declared) {
+        if max_body_bytes > 0 && declared > max_body_bytes as u64 {
+            let status = StatusCode::from_u16(custom.status).unwrap_or(StatusCode::PAYLOAD_TOO_LARGE);
+            return typed_response(status, &custom.response_type, custom.body);
+        }
+    }
+    next.run(request).await
+}
+
+fn is_health_path(state: &AppState, path: &str) -> bool {
+    let config = state.config.lock().unwrap();
+    !config.health_path.is_empty() && path == config.health_path
+}
+
+// Rejects requests without the configured Basic credentials. Reads the config per
+// request so changing the credentials doesn't need a restart.
+async fn basic_auth(State(state): State<AppState>, request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let expected = {
+        let config = state.config.lock().unwrap();
+        let user = config.basic_auth_user.clone().unwrap_or_default();
+        let pass = config.basic_auth_pass.clone().unwrap_or_default();
+        if user.is_empty() && pass.is_empty() {
+            None
         } else {
-            // Try ANY method
-            let any_key = format!("ANY {}", path);
-            mocks.get(&any_key).cloned()
+            Some(format!("{}:{}", user, pass))
         }
     };
+    // The health check stays reachable for probes without credentials
+    let Some(expected) = expected.filter(|_| !is_health_path(&state, request.uri().path())) else {
+        return next.run(request).await;
+    };
 
-    if let Some(mock) = mock_opt {
-        let response_body = mock.response_body.clone();
-        let status = StatusCode::from_u16(mock.status_code).unwrap_or(StatusCode::OK);
-        
-        return match mock.response_type.as_str() {
-            "json" => {
-                 match serde_json::from_str::<serde_json::Value>(&response_body) {
-                    Ok(json) => (status, Json(json)).into_response(),
-                    Err(_) => (status, response_body).into_response(),
-                 }
-            },
-            "html" => (status, Html(response_body)).into_response(),
-            "js" => {
-                // Execute JS code
-                // Use spawn_blocking to run JS logic without blocking async runtime
-                let body = body.clone();
-                let method = method.to_string();
-                let path = path.to_string();
-                let headers_vec: Vec<(String, String)> = headers.iter().filter_map(|(k, v)| {
-                    v.to_str().ok().map(|val| (k.to_string(), val.to_string()))
-                }).collect();
-                let db_connections = state.db_connections.clone();
-                
-                let result = tokio::task::spawn_blocking(move || {
-                    let mut context = Context::default();
-                    
-                    // Manually map missing MySql Tiny (i8) type support in Any driver
-                    // We can't easily change sqlx internals here.
-                    // But we can catch the specific error and try to explain?
-                    // No, the error happens inside fetch_all.
+    use base64::Engine;
+    let authorized = request.headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Basic "))
+        .and_then(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok())
+        .is_some_and(|decoded| decoded == expected.as_bytes());
+
+    if authorized {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(axum::http::header::WWW_AUTHENTICATE, "Basic realm=\"evo\"")],
+            "Unauthorized",
+        ).into_response()
+    }
+}
+
+// Header map as a JSON object for hook scripts; repeated headers are joined with ", "
+fn headers_json(headers: &HeaderMap) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for name in headers.keys() {
+        let values: Vec<&str> = headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).collect();
+        object.insert(name.to_string(), serde_json::Value::String(values.join(", ")));
+    }
+    serde_json::Value::Object(object)
+}
+
+fn hook_request_json(method: &Method, uri: &Uri, headers: &HeaderMap, body: &str) -> serde_json::Value {
+    serde_json::json!({
+        "method": method.as_str(),
+        "path": uri.path(),
+        "query": uri.query().unwrap_or_default(),
+        "headers": headers_json(headers),
+        "body": body,
+    })
+}
+
+// Take over what a pre-request script changed in the request object. Untouched parts are
+// kept as they were, so repeated headers survive a script that doesn't edit headers.
+fn apply_hook_request(modified: Option<serde_json::Value>, uri: Uri, headers: HeaderMap, body: String) -> (Uri, HeaderMap, String) {
+    let Some(modified) = modified else {
+        return (uri, headers, body);
+    };
+    let as_string = |v: &serde_json::Value| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+
+    let path = modified.get("path").map(as_string).unwrap_or_else(|| uri.path().to_string());
+    let query = modified.get("query").map(as_string).unwrap_or_else(|| uri.query().unwrap_or_default().to_string());
+    let uri = if path != uri.path() || query != uri.query().unwrap_or_default() {
+        let path_and_query = if query.is_empty() { path.clone() } else { format!("{}?{}", path, query) };
+        match path_and_query.parse::<Uri>() {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                println!("[JS Hook] Ignoring invalid request path '{}': {}", path_and_query, e);
+                uri
+            }
+        }
+    } else {
+        uri
+    };
+
+    let headers = match modified.get("headers").and_then(|h| h.as_object()) {
+        Some(modified_headers) if serde_json::Value::Object(modified_headers.clone()) != headers_json(&headers) => {
+            let mut rewritten = HeaderMap::new();
+            for (name, value) in modified_headers {
+                match (axum::http::HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&as_string(value))) {
+                    (Ok(name), Ok(value)) => {
+                        rewritten.append(name, value);
+                    }
+                    _ => println!("[JS Hook] Ignoring invalid request header '{}'", name),
+                }
+            }
+            rewritten
+        }
+        _ => headers,
+    };
+
+    let body = modified.get("body").map(as_string).unwrap_or(body);
+    (uri, headers, body)
+}
+
+// What a hook script left behind
+struct HookOutcome {
+    // The request object after the script ran
+    request: Option<serde_json::Value>,
+    // response.setHeader calls, in order
+    set_headers: Vec<(axum::http::HeaderName, HeaderValue)>,
+    // response.setStatusCode, only offered to post-response scripts
+    status: Option<u16>,
+}
+
+// run_hook_script on a blocking thread, giving up after js_timeout_ms like js mocks do
+async fn run_hook(script: String, request: serde_json::Value, response: Option<serde_json::Value>, js_timeout_ms: u64, app_handle: Option<tauri::AppHandle>) -> Result<HookOutcome, String> {
+    let hook = tokio::task::spawn_blocking(move || run_hook_script(&script, request, response, js_timeout_ms, app_handle));
+    if js_timeout_ms == 0 {
+        return hook.await.unwrap();
+    }
+    match tokio::time::timeout(std::time::Duration::from_millis(js_timeout_ms), hook).await {
+        Ok(result) => result.unwrap(),
+        Err(_) => Err(format!("timed out after {} ms", js_timeout_ms)),
+    }
+}
+
+// Run ServerConfig.pre_request_script (response None) or post_response_script in a fresh
+// JS context. Blocking, so callers go through run_hook.
+fn run_hook_script(script: &str, request: serde_json::Value, response: Option<serde_json::Value>, js_timeout_ms: u64, app_handle: Option<tauri::AppHandle>) -> Result<HookOutcome, String> {
+    use boa_engine::{JsResult, JsValue, NativeFunction};
+    use std::{cell::RefCell, rc::Rc};
+
+    let mut context = Context::default();
+    if js_timeout_ms > 0 {
+        context.runtime_limits_mut().set_loop_iteration_limit(js_timeout_ms.saturating_mul(JS_LOOP_ITERATIONS_PER_MS));
+    }
+    let set_headers: Rc<RefCell<Vec<(axum::http::HeaderName, HeaderValue)>>> = Default::default();
+    let status: Rc<RefCell<Option<u16>>> = Default::default();
+
+    let request_js = JsValue::from_json(&request, &mut context).map_err(|e| e.to_string())?;
+    context.register_global_property(boa_engine::JsString::from("request"), request_js, boa_engine::property::Attribute::all())
+        .map_err(|e| e.to_string())?;
+
+    let set_header = unsafe {
+        let set_headers = set_headers.clone();
+        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+            let name = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+            let value = args.get(1).cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+            // Invalid names or values are skipped rather than failing the script
+            if let (Ok(name), Ok(value)) = (axum::http::HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+                set_headers.borrow_mut().push((name, value));
+            }
+            Ok(JsValue::undefined())
+        })
+    };
+    let set_status_code = unsafe {
+        let status = status.clone();
+        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+            let code = args.first().cloned().unwrap_or_default().to_number(context)?;
+            if StatusCode::from_u16(code as u16).is_ok() {
+                *status.borrow_mut() = Some(code as u16);
+            }
+            Ok(JsValue::undefined())
+        })
+    };
+    let (status_js, headers_js) = match &response {
+        Some(response) => (
+            JsValue::from_json(response.get("status").unwrap_or(&serde_json::Value::Null), &mut context).map_err(|e| e.to_string())?,
+            JsValue::from_json(response.get("headers").unwrap_or(&serde_json::Value::Null), &mut context).map_err(|e| e.to_string())?,
+        ),
+        None => (JsValue::undefined(), JsValue::undefined()),
+    };
+    let mut response_init = boa_engine::object::ObjectInitializer::new(&mut context);
+    response_init.function(set_header, boa_engine::JsString::from("setHeader"), 2);
+    if response.is_some() {
+        response_init
+            .property(boa_engine::JsString::from("status"), status_js, boa_engine::property::Attribute::READONLY)
+            .property(boa_engine::JsString::from("headers"), headers_js, boa_engine::property::Attribute::READONLY)
+            .function(set_status_code, boa_engine::JsString::from("setStatusCode"), 1);
+    }
+    let response_js = response_init.build();
+    context.register_global_property(boa_engine::JsString::from("response"), response_js, boa_engine::property::Attribute::READONLY)
+        .map_err(|e| e.to_string())?;
+
+    // Same console as js mocks, with the hook's config field in place of the mock id
+    let hook = if response.is_some() { "post_response_script" } else { "pre_request_script" };
+    let console_js = js_console_object(&mut context, app_handle, ("hook", hook.to_string()));
+    context.register_global_property(boa_engine::JsString::from("console"), console_js, boa_engine::property::Attribute::READONLY)
+        .map_err(|e| e.to_string())?;
+
+    let code = format!("(function(request, response) {{\n{}\n}})(request, response);", script);
+    context.eval(Source::from_bytes(code.as_bytes())).map_err(|e| e.to_string())?;
+
+    let request = context.global_object()
+        .get(boa_engine::JsString::from("request"), &mut context)
+        .and_then(|request| request.to_json(&mut context))
+        .map_err(|e| e.to_string())?;
+    let set_headers = set_headers.borrow().clone();
+    let status = *status.borrow();
+    Ok(HookOutcome { request, set_headers, status })
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> Response {
+    let body = state.metrics.lock().unwrap().render();
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    ).into_response()
+}
+
+#[axum::debug_handler]
+async fn handler(
+    State(state): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    ws: Option<WebSocketUpgrade>,
+    body: Result<String, axum::extract::rejection::StringRejection>,
+) -> Response {
+    // The liveness probe always answers, ahead of the hooks, maintenance and mocks
+    if is_health_path(&state, uri.path()) {
+        let uptime_secs = state.started_at.lock().unwrap().map_or(0, |start| start.instant.elapsed().as_secs());
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "uptime_secs": uptime_secs }))).into_response();
+    }
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            let custom = state.config.lock().unwrap().max_body_response.clone();
+            return match custom {
+                Some(custom) => {
+                    let status = StatusCode::from_u16(custom.status).unwrap_or(StatusCode::PAYLOAD_TOO_LARGE);
+                    typed_response(status, &custom.response_type, custom.body)
+                }
+                None => rejection.into_response(),
+            };
+        }
+        Err(rejection) => return rejection.into_response(),
+    };
+    let (pre_request_script, post_response_script, js_timeout_ms) = {
+        let config = state.config.lock().unwrap();
+        let script = |s: &Option<String>| s.clone().filter(|s| !s.trim().is_empty());
+        (script(&config.pre_request_script), script(&config.post_response_script), config.js_timeout_ms)
+    };
+    // Headers the pre-request script set with response.setHeader
+    let mut hook_headers = Vec::new();
+    let (uri, headers, body) = match pre_request_script {
+        Some(script) => {
+            let request = hook_request_json(&method, &uri, &headers, &body);
+            let app_handle = state.app_handle.lock().unwrap().clone();
+            match run_hook(script, request, None, js_timeout_ms, app_handle).await {
+                Ok(outcome) => {
+                    hook_headers = outcome.set_headers;
+                    apply_hook_request(outcome.request, uri, headers, body)
+                }
+                Err(e) => {
+                    println!("[JS Hook] pre_request_script failed: {}", e);
+                    (uri, headers, body)
+                }
+            }
+        }
+        None => (uri, headers, body),
+    };
+    let hook_request = post_response_script.as_ref().map(|_| hook_request_json(&method, &uri, &headers, &body));
+
+    let start_time = Instant::now();
+    let request_body_clone = body.clone();
+
+    if let Ok(mut count) = state.request_count.lock() {
+        *count += 1;
+    }
+
+    let header_delay_ms = if state.config.lock().unwrap().honor_delay_header {
+        headers
+            .get("x-mock-delay")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|ms| ms.min(MAX_HEADER_DELAY_MS))
+    } else {
+        None
+    };
+    if let Some(ms) = header_delay_ms {
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+    }
+    
+    let log_sensitive_headers = state.config.lock().unwrap().log_sensitive_headers;
+    let request_headers = log_headers(&headers, log_sensitive_headers);
+
+    // Process request
+    let mut matched = None;
+    let mut response = process_request(state.clone(), method.clone(), uri.clone(), headers, ws, body, &mut matched).await;
+    let contract_violations = matched.as_mut().map(|m| std::mem::take(&mut m.contract_violations)).unwrap_or_default();
+    let proxy_target = matched.as_ref().and_then(|m| m.proxy_target.clone());
+    // MockApi.content_type beats whatever type the response branch chose
+    let content_type = matched.as_ref().and_then(|m| m.content_type.as_deref()).and_then(|c| HeaderValue::from_str(c).ok());
+    if let Some(content_type) = content_type {
+        response.headers_mut().insert(axum::http::header::CONTENT_TYPE, content_type);
+    }
+    if let Some(cors) = matched.as_ref().and_then(|m| m.cors.clone()) {
+        cors.apply(response.headers_mut());
+        response.extensions_mut().insert(cors);
+    }
+
+    if let (Some(script), Some(request)) = (post_response_script, hook_request) {
+        let hook_response = serde_json::json!({
+            "status": response.status().as_u16(),
+            "headers": headers_json(response.headers()),
+        });
+        let app_handle = state.app_handle.lock().unwrap().clone();
+        match run_hook(script, request, Some(hook_response), js_timeout_ms, app_handle).await {
+            Ok(outcome) => {
+                hook_headers.extend(outcome.set_headers);
+                if let Some(status) = outcome.status.and_then(|s| StatusCode::from_u16(s).ok()) {
+                    *response.status_mut() = status;
+                }
+            }
+            Err(e) => println!("[JS Hook] post_response_script failed: {}", e),
+        }
+    }
+    for (name, value) in hook_headers {
+        response.headers_mut().insert(name, value);
+    }
+
+    if state.config.lock().unwrap().debug_headers {
+        let matched = matched.unwrap_or_else(|| MatchedRoute {
+            id: "none".to_string(),
+            response_type: "none".to_string(),
+            ..Default::default()
+        });
+        let debug_headers = [
+            ("x-mock-matched-id", Some(matched.id)),
+            ("x-mock-response-type", Some(matched.response_type)),
+            ("x-mock-proxy-target", matched.proxy_target),
+        ];
+        for (name, value) in debug_headers {
+            if let Some(value) = value.and_then(|v| HeaderValue::from_str(&v).ok()) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+    
+    // Log request
+    let elapsed = start_time.elapsed();
+    let duration = elapsed.as_millis() as u64;
+    {
+        let buckets = state.config.lock().unwrap().metrics_buckets.clone();
+        if let Ok(mut metrics) = state.metrics.lock() {
+            metrics.observe(&buckets, elapsed.as_secs_f64() * 1000.0);
+        }
+    }
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let status_code = response.status().as_u16();
+    
+    // Try to capture response body for logging
+    // Note: This consumes the response body, so we need to reconstruct it.
+    // For now, let's just log "Response body logged" placeholder or try to peek if possible.
+    // Actually, we can read the bytes, store them, and create a new body.
+    
+    let (parts, body) = response.into_parts();
+    // Streamed bodies (proxied downloads, streaming scripts) pass through untouched
+    // and are logged as a placeholder
+    let (body, response_body_str) = if parts.extensions.get::<StreamedBody>().is_some() {
+        (body, Some("[streamed response]".to_string()))
+    } else {
+        let bytes = to_bytes(body, usize::MAX).await.unwrap_or_default();
+        let response_body_str = String::from_utf8(bytes.to_vec()).ok();
+        (Body::from(bytes), response_body_str)
+    };
+    
+    let log = RequestLog {
+        id: uuid::Uuid::new_v4().to_string(),
+        method: method.to_string(),
+        path: uri.path().to_string(),
+        status_code,
+        duration_ms: duration,
+        timestamp,
+        request_body: Some(request_body_clone),
+        response_body: response_body_str.clone(),
+        variant: parts.extensions.get::<ServedVariant>().map(|v| v.0),
+        contract_violations,
+        header_delay_ms,
+        proxy_target,
+        request_headers,
+        response_headers: log_headers(&parts.headers, log_sensitive_headers),
+    };
+    
+    // Store log
+    let log_limit = state.config.lock().unwrap().log_limit.min(MAX_LOG_LIMIT);
+    if let Ok(mut logs) = state.logs.lock() {
+        logs.push_front(log.clone());
+        logs.truncate(log_limit);
+    }
+
+    let use_log_db = state.config.lock().unwrap().log_store == "sqlite";
+    if use_log_db {
+        if let Some(pool) = state.log_db.lock().unwrap().clone() {
+            let log = log.clone();
+            tokio::spawn(async move {
+                if let Err(e) = insert_log_record(&pool, &log).await {
+                    println!("Failed to store log: {}", e);
+                }
+            });
+        }
+    }
+    
+    // Emit event
+    if let Ok(handle_guard) = state.app_handle.lock() {
+        if let Some(app_handle) = handle_guard.as_ref() {
+             use tauri::Emitter;
+             if let Err(e) = app_handle.emit("new-request-log", log.clone()) {
+                 println!("Failed to emit log: {}", e);
+             }
+        }
+    }
+    
+    // Reconstruct response
+    let response = Response::from_parts(parts, body);
+    response
+}
+
+async fn process_request(
+    state: AppState,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    ws: Option<WebSocketUpgrade>,
+    body: String,
+    matched: &mut Option<MatchedRoute>,
+) -> Response {
+    let path = uri.path();
+    let key = mock_key(method.as_str(), path, None, None, None, None, None);
+    let match_request = MatchRequest::new(&state, method.as_str(), path, uri.query(), &headers, &body);
+
+    let environment = {
+        let active = state.config.lock().unwrap().active_environment.clone();
+        active.and_then(|name| state.environments.lock().unwrap().get(&name).cloned())
+    }.unwrap_or_default();
+
+    let maintenance = state.config.lock().unwrap().maintenance.clone();
+    if let Some(maintenance) = maintenance {
+        if !maintenance_allows(&maintenance, path) {
+            let status = StatusCode::from_u16(maintenance.status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+            return typed_response(status, &maintenance.response_type, maintenance.body);
+        }
+    }
+
+    // Global quota is cumulative: once exhausted every request gets the quota response
+    // until `reset_global_quota` is called.
+    let quota = state.config.lock().unwrap().global_quota.clone();
+    if let Some(quota) = quota {
+        let count = *state.request_count.lock().unwrap();
+        if count > quota.limit {
+            let status = StatusCode::from_u16(quota.status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+            return (
+                status,
+                [(axum::http::header::RETRY_AFTER, quota.retry_after_secs.to_string())],
+                quota.body,
+            ).into_response();
+        }
+    }
+    
+    let (chaos_delay_ms_max, chaos_failure_rate) = {
+        let config = state.config.lock().unwrap();
+        (config.chaos_delay_ms_max, config.chaos_failure_rate)
+    };
+    if chaos_delay_ms_max > 0 || chaos_failure_rate > 0.0 {
+        let (fail, delay_ms) = {
+            let mut rng = state.rng.lock().unwrap();
+            (rng.random::<f32>() < chaos_failure_rate, rng.random_range(0..=chaos_delay_ms_max))
+        };
+        if fail {
+            return (StatusCode::INTERNAL_SERVER_ERROR, "chaos injected").into_response();
+        }
+        if delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        }
+    }
+
+    // Check exact match first, then param, regex and wildcard mocks
+    let (mock_opt, params) = match select_mock(&state, &match_request) {
+        Some((mock, params, _)) => (Some(mock), params),
+        None => (None, HashMap::new()),
+    };
+
+    if let Some(mock) = mock_opt {
+        *matched = Some(MatchedRoute {
+            id: mock.id.clone(),
+            response_type: mock.response_type.clone(),
+            proxy_target: None,
+            contract_violations: mock.expected_request.as_ref()
+                .map(|expected| check_expected_request(expected, &headers, uri.query(), &body))
+                .unwrap_or_default(),
+            content_type: None,
+            cors: MockCors::for_mock(&mock, &headers),
+        });
+        if let Some(rejection) = request_schema_rejection(&mock, &headers, &body) {
+            return rejection;
+        }
+        // The 400 above keeps its JSON type; everything from here on gets the mock's
+        if let Some(matched) = matched.as_mut() {
+            matched.content_type = mock.content_type.clone();
+        }
+        if let Some(exhausted) = count_hit(&state, &mock, &key) {
+            return exhausted;
+        }
+
+        let variant = pick_weighted(&mock.weighted_responses, &state.rng);
+        let (status_code, response_body) = match variant {
+            Some(i) => (mock.weighted_responses[i].status, mock.weighted_responses[i].body.clone()),
+            None => (mock.status_code, mock.response_body.clone()),
+        };
+        let (response_type, status_code, response_body) = if mock.response_type == "sequence" {
+            match next_sequence_step(&state, &mock) {
+                Ok(step) => {
+                    let body = match step.body {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (step.response_type, step.status_code, body)
+                }
+                Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+            }
+        } else {
+            (mock.response_type.clone(), status_code, response_body)
+        };
+        let status_code = toggle_status(&state, &mock).unwrap_or(status_code);
+        let response_body = if mock.match_type == "regex" || mock.match_type == "param" {
+            expand_params(&response_body, &params)
+        } else {
+            response_body
+        };
+        let response_body = resolve_vars(&response_body, &environment.variables);
+        let frozen_time = state.config.lock().unwrap().frozen_time;
+        let response_body = if mock.templated && matches!(response_type.as_str(), "json" | "html" | "raw") {
+            let now = frozen_time.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64);
+            render_template(&response_body, &TemplateContext {
+                method: method.as_str(),
+                path,
+                query: &match_request.query,
+                headers: &headers,
+                params: &params,
+                now_ms: now,
+                rng: &state.rng,
+            })
+        } else {
+            response_body
+        };
+        let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+        
+        let mut response = match response_type.as_str() {
+            "json" => {
+                 match serde_json::from_str::<serde_json::Value>(&response_body) {
+                    Ok(json) => (status, Json(json)).into_response(),
+                    Err(_) => (status, response_body).into_response(),
+                 }
+            },
+            "html" => (status, Html(response_body)).into_response(),
+            "js" => {
+                let response_body = mock.response_body.clone();
+                let params = params.clone();
+                // Execute JS code
+                // Use spawn_blocking to run JS logic without blocking async runtime
+                let body = body.clone();
+                let method = method.to_string();
+                let path = path.to_string();
+                // Decoded query parameters in first-seen order; repeated keys collect every value
+                let mut query: Vec<(String, Vec<String>)> = Vec::new();
+                for (k, v) in url::form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes()).into_owned() {
+                    match query.iter_mut().find(|(key, _)| *key == k) {
+                        Some((_, values)) => values.push(v),
+                        None => query.push((k, vec![v])),
+                    }
+                }
+                let header_allowlist = state.config.lock().unwrap().js_header_allowlist.clone();
+                let headers_vec: Vec<(String, String)> = headers.iter().filter(|(k, _)| {
+                    // HeaderName is always lowercase, so only the allowlist needs folding
+                    header_allowlist.as_ref().is_none_or(|allowed| allowed.iter().any(|a| a.eq_ignore_ascii_case(k.as_str())))
+                }).filter_map(|(k, v)| {
+                    v.to_str().ok().map(|val| (k.to_string(), val.to_string()))
+                }).collect();
+                let db_connections = state.db_connections.clone();
+                let kv = state.kv.clone();
+                // Clone the handle out of the mutex; the JS closure runs on a blocking thread
+                let app_handle = state.app_handle.lock().unwrap().clone();
+                // Sent with console output so the frontend can tell scripts apart
+                let mock_id = mock.id.clone();
+                // fetch() shares the proxy client and its TLS settings
+                let fetch_client = state.proxy_client.lock().unwrap().clone();
+                // Headers set by the script via response.setHeader, merged in afterwards
+                let js_headers = Arc::new(Mutex::new(HeaderMap::new()));
+                let js_headers_ref = js_headers.clone();
+                let js_cookies_ref = js_headers.clone();
+                // request.cookies, parsed from the Cookie header unless the allowlist hides it
+                let cookie_allowed = header_allowlist.as_ref().is_none_or(|allowed| allowed.iter().any(|a| a.eq_ignore_ascii_case("cookie")));
+                let cookies: Vec<(String, String)> = headers
+                    .get_all(axum::http::header::COOKIE)
+                    .iter()
+                    .filter(|_| cookie_allowed)
+                    .filter_map(|v| v.to_str().ok())
+                    .flat_map(|v| v.split(';'))
+                    .filter_map(|pair| {
+                        let (name, value) = pair.trim().split_once('=')?;
+                        Some((name.trim().to_string(), value.trim().trim_matches('"').to_string()))
+                    })
+                    .collect();
+                
+                // response.write/flush switch the script to a streamed response: the first
+                // call sends the status and headers set so far, later chunks follow as written
+                let (stream_tx, mut stream_rx) = tokio::sync::mpsc::unbounded_channel::<axum::body::Bytes>();
+                let (stream_start_tx, stream_start_rx) = tokio::sync::oneshot::channel::<StatusCode>();
+                let stream_tx = Arc::new(Mutex::new(Some(stream_tx)));
+                let stream_start = Arc::new(Mutex::new(Some(stream_start_tx)));
+                let stream_tx_ref = stream_tx.clone();
+                let js_timeout_ms = state.config.lock().unwrap().js_timeout_ms;
+                
+                let script = tokio::task::spawn_blocking(move || {
+                    let mut context = match frozen_time {
+                        Some(ms) => Context::builder()
+                            .clock(std::rc::Rc::new(boa_engine::context::time::FixedClock::from_millis(ms.max(0) as u64)))
+                            .build()
+                            .unwrap_or_default(),
+                        None => Context::default(),
+                    };
+                    let deadline = (js_timeout_ms > 0).then(|| Instant::now() + std::time::Duration::from_millis(js_timeout_ms));
+                    if js_timeout_ms > 0 {
+                        context.runtime_limits_mut().set_loop_iteration_limit(js_timeout_ms.saturating_mul(JS_LOOP_ITERATIONS_PER_MS));
+                    }
+                    
+                    // Manually map missing MySql Tiny (i8) type support in Any driver
+                    // We can't easily change sqlx internals here.
+                    // But we can catch the specific error and try to explain?
+                    // No, the error happens inside fetch_all.
                     // The only way is to use a specific pool (MySqlPool) if we know it's MySQL.
                     // But we are using AnyPool for flexibility.
                     // Wait, sqlx 0.8 Any driver *should* support basic types.
@@ -262,6 +1688,46 @@ async fn process_request(
                     }
                     let headers_js = headers_obj.build();
 
+                    let mut params_obj = boa_engine::object::ObjectInitializer::new(&mut context);
+                    for (k, v) in params {
+                        params_obj.property(
+                            boa_engine::JsString::from(k),
+                            boa_engine::JsString::from(v),
+                            boa_engine::property::Attribute::READONLY
+                        );
+                    }
+                    let params_js = params_obj.build();
+
+                    let mut cookies_obj = boa_engine::object::ObjectInitializer::new(&mut context);
+                    for (k, v) in cookies {
+                        cookies_obj.property(
+                            boa_engine::JsString::from(k),
+                            boa_engine::JsString::from(v),
+                            boa_engine::property::Attribute::READONLY
+                        );
+                    }
+                    let cookies_js = cookies_obj.build();
+
+                    // request.query.key is a string, or an array of strings for repeated keys
+                    let query_values: Vec<(String, JsValue)> = query.into_iter().map(|(k, mut values)| {
+                        let value = if values.len() == 1 {
+                            JsValue::from(boa_engine::JsString::from(values.remove(0)))
+                        } else {
+                            let items = values.into_iter().map(|v| JsValue::from(boa_engine::JsString::from(v)));
+                            boa_engine::object::builtins::JsArray::from_iter(items, &mut context).into()
+                        };
+                        (k, value)
+                    }).collect();
+                    let mut query_obj = boa_engine::object::ObjectInitializer::new(&mut context);
+                    for (k, v) in query_values {
+                        query_obj.property(
+                            boa_engine::JsString::from(k),
+                            v,
+                            boa_engine::property::Attribute::READONLY
+                        );
+                    }
+                    let query_js = query_obj.build();
+
                     let request_obj = boa_engine::object::ObjectInitializer::new(&mut context)
                         .property(
                             boa_engine::JsString::from("headers"),
@@ -283,6 +1749,21 @@ async fn process_request(
                             boa_engine::JsString::from(path),
                             boa_engine::property::Attribute::READONLY
                         )
+                        .property(
+                            boa_engine::JsString::from("params"),
+                            params_js,
+                            boa_engine::property::Attribute::READONLY
+                        )
+                        .property(
+                            boa_engine::JsString::from("query"),
+                            query_js,
+                            boa_engine::property::Attribute::READONLY
+                        )
+                        .property(
+                            boa_engine::JsString::from("cookies"),
+                            cookies_js,
+                            boa_engine::property::Attribute::READONLY
+                        )
                         .build();
 
                     if let Err(e) = context.register_global_property(
@@ -307,12 +1788,10 @@ async fn process_request(
                         NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
                             let conn_name = args.get(0).and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing connection name"))))?;
                             let sql = args.get(1).and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing SQL"))))?;
-                            let _params_val = args.get(2); // Optional params array
+                            let params = js_sql_params(args.get(2), context)?; // Optional params array
     
                             let conn_name_str = conn_name.to_std_string().unwrap();
                             let sql_str = sql.to_std_string().unwrap();
-                            
-                            // Extract params (skipped for now)
     
                             // Execute query in blocking thread
                             // Since we are already in spawn_blocking, we can use block_on locally?
@@ -337,39 +1816,12 @@ async fn process_request(
                                      if let Some(pool) = pool {
                                          println!("[DB] Executing query on '{}': {}", conn_name_str, sql_str);
                                          
-                                         let rows = sqlx::query(&sql_str)
+                                         let rows = bind_sql_params(sqlx::query(&sql_str), &params)?
                                              .fetch_all(&pool)
                                              .await
-                                             .map_err(|e| e.to_string())?;
+                                             .map_err(|e| any_driver_error_hint(e.to_string()))?;
                                          
-                                         // Convert rows to JSON
-                                         let mut json_rows = Vec::new();
-                                         for row in rows {
-                                             use sqlx::{Row, Column};
-                                             let mut row_obj = serde_json::Map::new();
-                                             for col in row.columns() {
-                                                let name = col.name();
-                                                let val_json = if let Ok(v) = row.try_get::<String, _>(name) {
-                                                serde_json::Value::String(v)
-                                            } else if let Ok(v) = row.try_get::<i64, _>(name) {
-                                                serde_json::Value::Number(v.into())
-                                            } else if let Ok(v) = row.try_get::<f64, _>(name) {
-                                                serde_json::Number::from_f64(v).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null)
-                                            } else if let Ok(v) = row.try_get::<bool, _>(name) {
-                                                serde_json::Value::Bool(v)
-                                            // Any driver doesn't support i8 directly, map to i16 or i32
-                                            } else if let Ok(v) = row.try_get::<i16, _>(name) {
-                                                serde_json::Value::Number(v.into())
-                                            } else if let Ok(v) = row.try_get::<i32, _>(name) {
-                                                serde_json::Value::Number(v.into())
-                                            } else {
-                                                serde_json::Value::Null
-                                            };
-                                                row_obj.insert(name.to_string(), val_json);
-                                            }
-                                             json_rows.push(serde_json::Value::Object(row_obj));
-                                         }
-                                         Ok(json_rows)
+                                         Ok(rows.iter().map(sql_row_to_json).collect::<Vec<_>>())
                                      } else {
                                          Err(format!("Connection '{}' not found", conn_name_str))
                                      }
@@ -391,9 +1843,10 @@ async fn process_request(
                     
                     let execute_fn = unsafe {
                         let db_connections = db_connections_ref.clone();
-                        NativeFunction::from_closure(move |_this, args, _context| -> JsResult<JsValue> {
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
                              let conn_name = args.get(0).and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing connection name"))))?;
                              let sql = args.get(1).and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing SQL"))))?;
+                             let params = js_sql_params(args.get(2), context)?;
                              
                              let conn_name_str = conn_name.to_std_string().unwrap();
                              let sql_str = sql.to_std_string().unwrap();
@@ -413,7 +1866,7 @@ async fn process_request(
     
                                      if let Some(pool) = pool {
                                          println!("[DB] Executing command on '{}': {}", conn_name_str, sql_str);
-                                         let result = sqlx::query(&sql_str)
+                                         let result = bind_sql_params(sqlx::query(&sql_str), &params)?
                                              .execute(&pool)
                                              .await
                                              .map_err(|e| e.to_string())?;
@@ -431,9 +1884,95 @@ async fn process_request(
                         })
                     };
     
+                    // db.begin(connName) returns { query, execute, commit, rollback } bound to one
+                    // transaction. Transactions still open when the script ends (or throws)
+                    // are rolled back.
+                    let transactions: std::rc::Rc<std::cell::RefCell<Vec<std::rc::Rc<JsTransaction>>>> = Default::default();
+                    let begin_fn = unsafe {
+                        let db_connections = db_connections_ref.clone();
+                        let transactions = transactions.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let js_error = |e: String| JsError::from_opaque(JsValue::new(boa_engine::JsString::from(e)));
+                            let conn_name = args.first().and_then(|v| v.as_string()).ok_or_else(|| js_error("Missing connection name".to_string()))?;
+                            let conn_name = conn_name.to_std_string().unwrap_or_default();
+                            let pool = db_connections.lock().unwrap().get(&conn_name).cloned()
+                                .ok_or_else(|| js_error(format!("Connection '{}' not found", conn_name)))?;
+
+                            let rt = tokio::runtime::Builder::new_current_thread()
+                                .enable_all()
+                                .build()
+                                .map_err(|e| js_error(e.to_string()))?;
+                            println!("[DB] Beginning transaction on '{}'", conn_name);
+                            let tx = rt.block_on(pool.begin()).map_err(|e| js_error(e.to_string()))?;
+                            let transaction = std::rc::Rc::new(JsTransaction { rt, tx: std::cell::RefCell::new(Some(tx)) });
+                            transactions.borrow_mut().push(transaction.clone());
+
+                            let closed = move || js_error("Transaction is already finished".to_string());
+                            let tx_query = {
+                                let transaction = transaction.clone();
+                                NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                                    let sql = args.first().and_then(|v| v.as_string()).ok_or_else(|| js_error("Missing SQL".to_string()))?;
+                                    let sql = sql.to_std_string().unwrap_or_default();
+                                    let params = js_sql_params(args.get(1), context)?;
+                                    let mut tx = transaction.tx.borrow_mut();
+                                    let tx = tx.as_mut().ok_or_else(closed)?;
+                                    println!("[DB] Executing query in transaction: {}", sql);
+                                    let rows = transaction.rt.block_on(async {
+                                        bind_sql_params(sqlx::query(&sql), &params)?
+                                            .fetch_all(&mut **tx)
+                                            .await
+                                            .map_err(|e| any_driver_error_hint(e.to_string()))
+                                    }).map_err(js_error)?;
+                                    JsValue::from_json(&serde_json::Value::Array(rows.iter().map(sql_row_to_json).collect()), context)
+                                })
+                            };
+                            let tx_execute = {
+                                let transaction = transaction.clone();
+                                NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                                    let sql = args.first().and_then(|v| v.as_string()).ok_or_else(|| js_error("Missing SQL".to_string()))?;
+                                    let sql = sql.to_std_string().unwrap_or_default();
+                                    let params = js_sql_params(args.get(1), context)?;
+                                    let mut tx = transaction.tx.borrow_mut();
+                                    let tx = tx.as_mut().ok_or_else(closed)?;
+                                    println!("[DB] Executing command in transaction: {}", sql);
+                                    let result = transaction.rt.block_on(async {
+                                        bind_sql_params(sqlx::query(&sql), &params)?
+                                            .execute(&mut **tx)
+                                            .await
+                                            .map_err(|e| e.to_string())
+                                    }).map_err(js_error)?;
+                                    Ok(JsValue::new(result.rows_affected() as i32))
+                                })
+                            };
+                            let tx_commit = {
+                                let transaction = transaction.clone();
+                                NativeFunction::from_closure(move |_this, _args, _context| -> JsResult<JsValue> {
+                                    let tx = transaction.tx.borrow_mut().take().ok_or_else(closed)?;
+                                    transaction.rt.block_on(tx.commit()).map_err(|e| js_error(e.to_string()))?;
+                                    Ok(JsValue::undefined())
+                                })
+                            };
+                            let tx_rollback = NativeFunction::from_closure(move |_this, _args, _context| -> JsResult<JsValue> {
+                                if !transaction.rollback_if_open().map_err(js_error)? {
+                                    return Err(closed());
+                                }
+                                Ok(JsValue::undefined())
+                            });
+
+                            let handle = boa_engine::object::ObjectInitializer::new(context)
+                                .function(tx_query, boa_engine::JsString::from("query"), 1)
+                                .function(tx_execute, boa_engine::JsString::from("execute"), 1)
+                                .function(tx_commit, boa_engine::JsString::from("commit"), 0)
+                                .function(tx_rollback, boa_engine::JsString::from("rollback"), 0)
+                                .build();
+                            Ok(handle.into())
+                        })
+                    };
+
                     let db_obj = boa_engine::object::ObjectInitializer::new(&mut context)
                         .function(query_fn, boa_engine::JsString::from("query"), 2)
                         .function(execute_fn, boa_engine::JsString::from("execute"), 2)
+                        .function(begin_fn, boa_engine::JsString::from("begin"), 1)
                         .build();
                     
                     if let Err(e) = context.register_global_property(
@@ -459,46 +1998,93 @@ async fn process_request(
                         })
                     };
     
-                    // --- Console Object ---
-                    let console_log = unsafe {
-                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
-                            let mut output = String::new();
-                            for (i, arg) in args.iter().enumerate() {
-                                if i > 0 {
-                                    output.push(' ');
-                                }
-                                
-                                if arg.is_string() {
-                                    output.push_str(&arg.as_string().unwrap().to_std_string().unwrap());
-                                } else {
-                                    let json_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("JSON"));
-                                    let stringify_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("stringify"));
-                                    
-                                    if let Ok(json_obj) = context.global_object().get(json_key, context) {
-                                        if let Some(json_obj) = json_obj.as_object() {
-                                             if let Ok(stringify) = json_obj.get(stringify_key, context) {
-                                                 if let Ok(s) = stringify.as_callable().unwrap().call(&JsValue::from(json_obj.clone()), &[arg.clone()], context) {
-                                                     if let Some(str_val) = s.as_string() {
-                                                         if let Ok(utf8) = str_val.to_std_string() {
-                                                             output.push_str(&utf8);
-                                                             continue;
-                                                         }
-                                                     }
-                                                 }
-                                             }
-                                        }
-                                    }
-                                    output.push_str(&format!("{:?}", arg));
+                    let set_header = unsafe {
+                        NativeFunction::from_closure(move |_this, args, _ctx| -> JsResult<JsValue> {
+                            let name = args.first().and_then(|v| v.as_string()).and_then(|s| s.to_std_string().ok());
+                            let value = args.get(1).and_then(|v| v.as_string()).and_then(|s| s.to_std_string().ok());
+                            // Invalid names or values are skipped rather than failing the script
+                            if let (Some(name), Some(value)) = (name, value) {
+                                if let (Ok(name), Ok(value)) = (axum::http::HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+                                    js_headers_ref.lock().unwrap().insert(name, value);
                                 }
                             }
-                            println!("[JS Console] {}", output);
                             Ok(JsValue::undefined())
                         })
                     };
     
-                    let console_obj = boa_engine::object::ObjectInitializer::new(&mut context)
-                        .function(console_log, boa_engine::JsString::from("log"), 0)
-                        .build();
+                    // setCookie(name, value, { path, maxAge, domain, sameSite, httpOnly, secure })
+                    let set_cookie = unsafe {
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let name = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            let value = args.get(1).cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            let mut cookie = format!("{}={}", name, value);
+                            if let Some(options) = args.get(2).and_then(|v| v.as_object()) {
+                                let mut option = |key: &str| options.get(boa_engine::JsString::from(key), context);
+                                let path = option("path")?;
+                                let max_age = option("maxAge")?;
+                                let domain = option("domain")?;
+                                let same_site = option("sameSite")?;
+                                let http_only = option("httpOnly")?;
+                                let secure = option("secure")?;
+                                if let Some(path) = path.as_string() {
+                                    cookie.push_str(&format!("; Path={}", path.to_std_string_escaped()));
+                                }
+                                if let Some(max_age) = max_age.as_number() {
+                                    cookie.push_str(&format!("; Max-Age={}", max_age as i64));
+                                }
+                                if let Some(domain) = domain.as_string() {
+                                    cookie.push_str(&format!("; Domain={}", domain.to_std_string_escaped()));
+                                }
+                                if let Some(same_site) = same_site.as_string() {
+                                    cookie.push_str(&format!("; SameSite={}", same_site.to_std_string_escaped()));
+                                }
+                                if http_only.to_boolean() {
+                                    cookie.push_str("; HttpOnly");
+                                }
+                                if secure.to_boolean() {
+                                    cookie.push_str("; Secure");
+                                }
+                            }
+                            // Invalid cookies are skipped rather than failing the script
+                            if let Ok(value) = HeaderValue::from_str(&cookie) {
+                                js_cookies_ref.lock().unwrap().append(axum::http::header::SET_COOKIE, value);
+                            }
+                            Ok(JsValue::undefined())
+                        })
+                    };
+    
+                    // write(chunk) streams a chunk straight to the client; flush() sends the
+                    // status and headers without a chunk, e.g. to open an SSE connection
+                    let start_stream = {
+                        let status_code_ref = status_code_ref.clone();
+                        move || {
+                            if let Some(start) = stream_start.lock().unwrap().take() {
+                                let _ = start.send(*status_code_ref.lock().unwrap());
+                            }
+                        }
+                    };
+                    let flush_stream = start_stream.clone();
+                    let write_fn = unsafe {
+                        let stream_tx = stream_tx_ref.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let chunk = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            start_stream();
+                            if let Some(tx) = stream_tx.lock().unwrap().as_ref() {
+                                // The client may have gone away; the script keeps running regardless
+                                let _ = tx.send(axum::body::Bytes::from(chunk));
+                            }
+                            Ok(JsValue::undefined())
+                        })
+                    };
+                    let flush_fn = unsafe {
+                        NativeFunction::from_closure(move |_this, _args, _ctx| -> JsResult<JsValue> {
+                            flush_stream();
+                            Ok(JsValue::undefined())
+                        })
+                    };
+    
+                    // --- Console Object ---
+                    let console_obj = js_console_object(&mut context, app_handle.clone(), ("mock_id", mock_id.clone()));
     
                     if let Err(e) = context.register_global_property(
                         boa_engine::JsString::from("console"),
@@ -508,12 +2094,148 @@ async fn process_request(
                          return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
                     }
     
+                    // --- App Object ---
+                    let emit_fn = unsafe {
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let event = args.first().and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing event name"))))?;
+                            let event = event.to_std_string().unwrap_or_default();
+                            // Same character set Tauri accepts for event names
+                            if event.is_empty() || !event.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_')) {
+                                return Err(JsError::from_opaque(JsValue::new(boa_engine::JsString::from(format!("Invalid event name '{}'", event)))));
+                            }
+
+                            let payload = match args.get(1) {
+                                Some(value) => value.to_json(context)?.unwrap_or(serde_json::Value::Null),
+                                None => serde_json::Value::Null,
+                            };
+
+                            // Script events are namespaced, so a script can't fake the app's own
+                            // events (new-request-log, server-config-changed, ...)
+                            if let Some(app_handle) = app_handle.as_ref() {
+                                use tauri::Emitter;
+                                app_handle.emit(&format!("mock:{}", event), payload).map_err(|e| JsError::from_opaque(JsValue::new(boa_engine::JsString::from(e.to_string()))))?;
+                            }
+                            Ok(JsValue::undefined())
+                        })
+                    };
+
+                    let app_obj = boa_engine::object::ObjectInitializer::new(&mut context)
+                        .function(emit_fn, boa_engine::JsString::from("emit"), 2)
+                        .build();
+
+                    if let Err(e) = context.register_global_property(
+                        boa_engine::JsString::from("app"),
+                        app_obj,
+                        boa_engine::property::Attribute::READONLY
+                    ) {
+                         return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
+                    }
+
+                    // --- fetch(url, { method, headers, body }) ---
+                    // Synchronous: returns { status, ok, headers, text(), json() }
+                    let fetch_fn = unsafe {
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let url = args.first().and_then(|v| v.as_string()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("Missing URL"))))?;
+                            let url = url.to_std_string().unwrap_or_default();
+                            let options = match args.get(1) {
+                                Some(value) if value.is_object() => value.to_json(context)?.unwrap_or(serde_json::Value::Null),
+                                _ => serde_json::Value::Null,
+                            };
+
+                            let method = options.get("method").and_then(|m| m.as_str()).unwrap_or("GET").to_uppercase();
+                            let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| JsError::from_opaque(JsValue::new(boa_engine::JsString::from(e.to_string()))))?;
+                            let mut request = fetch_client.request(method, &url);
+                            if let Some(headers) = options.get("headers").and_then(|h| h.as_object()) {
+                                for (k, v) in headers {
+                                    let value = v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+                                    request = request.header(k.as_str(), value);
+                                }
+                            }
+                            match options.get("body") {
+                                Some(serde_json::Value::String(body)) => request = request.body(body.clone()),
+                                Some(serde_json::Value::Null) | None => {}
+                                Some(body) => request = request.body(body.to_string()),
+                            }
+
+                            println!("[FETCH] {}", url);
+                            let result = {
+                                let rt = tokio::runtime::Builder::new_current_thread()
+                                    .enable_all()
+                                    .build()
+                                    .unwrap();
+                                rt.block_on(async {
+                                    let res = request.send().await.map_err(|e| e.to_string())?;
+                                    let status = res.status().as_u16();
+                                    let headers: Vec<(String, String)> = res.headers().iter()
+                                        .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.to_string(), v.to_string())))
+                                        .collect();
+                                    let text = res.text().await.map_err(|e| e.to_string())?;
+                                    Ok::<_, String>((status, headers, text))
+                                })
+                            };
+                            let (status, headers, text) = result.map_err(|e| JsError::from_opaque(JsValue::new(boa_engine::JsString::from(format!("fetch failed: {}", e)))))?;
+
+                            let mut headers_obj = boa_engine::object::ObjectInitializer::new(context);
+                            for (k, v) in headers {
+                                headers_obj.property(
+                                    boa_engine::JsString::from(k),
+                                    boa_engine::JsString::from(v),
+                                    boa_engine::property::Attribute::READONLY
+                                );
+                            }
+                            let headers_js = headers_obj.build();
+
+                            let body_text = text.clone();
+                            let text_fn = NativeFunction::from_closure(move |_this, _args, _context| -> JsResult<JsValue> {
+                                Ok(JsValue::new(boa_engine::JsString::from(body_text.clone())))
+                            });
+                            let json_fn = NativeFunction::from_closure(move |_this, _args, context| -> JsResult<JsValue> {
+                                let json = serde_json::from_str::<serde_json::Value>(&text)
+                                    .map_err(|e| JsError::from_opaque(JsValue::new(boa_engine::JsString::from(format!("Invalid JSON: {}", e)))))?;
+                                JsValue::from_json(&json, context)
+                            });
+
+                            let response = boa_engine::object::ObjectInitializer::new(context)
+                                .property(boa_engine::JsString::from("status"), status, boa_engine::property::Attribute::READONLY)
+                                .property(boa_engine::JsString::from("ok"), (200..300).contains(&status), boa_engine::property::Attribute::READONLY)
+                                .property(boa_engine::JsString::from("headers"), headers_js, boa_engine::property::Attribute::READONLY)
+                                .function(text_fn, boa_engine::JsString::from("text"), 0)
+                                .function(json_fn, boa_engine::JsString::from("json"), 0)
+                                .build();
+                            Ok(response.into())
+                        })
+                    };
+
+                    if let Err(e) = context.register_global_callable(boa_engine::JsString::from("fetch"), 2, fetch_fn) {
+                         return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
+                    }
+
                     let response_obj = boa_engine::object::ObjectInitializer::new(&mut context)
                         .function(
                             set_status_code,
                             boa_engine::JsString::from("setStatusCode"),
                             1
                         )
+                        .function(
+                            set_header,
+                            boa_engine::JsString::from("setHeader"),
+                            2
+                        )
+                        .function(
+                            set_cookie,
+                            boa_engine::JsString::from("setCookie"),
+                            3
+                        )
+                        .function(
+                            write_fn,
+                            boa_engine::JsString::from("write"),
+                            1
+                        )
+                        .function(
+                            flush_fn,
+                            boa_engine::JsString::from("flush"),
+                            0
+                        )
                         .build();
     
                     if let Err(e) = context.register_global_property(
@@ -524,16 +2246,102 @@ async fn process_request(
                          return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
                     }
                     
-                    let code = format!(
-                        "
-                        (function(request) {{
-                            {}
-                        }})(request);
-                        ",
-                        response_body
-                    );
+                    // --- Key/value store ---
+                    let kv_get = unsafe {
+                        let kv = kv.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let key = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            Ok(match kv.lock().unwrap().get(&key) {
+                                Some(value) => JsValue::new(boa_engine::JsString::from(value.as_str())),
+                                None => JsValue::null(),
+                            })
+                        })
+                    };
+                    let kv_set = unsafe {
+                        let kv = kv.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let key = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            let value = args.get(1).cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            kv.lock().unwrap().insert(key, value);
+                            Ok(JsValue::undefined())
+                        })
+                    };
+                    let kv_delete = unsafe {
+                        let kv = kv.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let key = args.first().cloned().unwrap_or_default().to_string(context)?.to_std_string_escaped();
+                            Ok(JsValue::new(kv.lock().unwrap().remove(&key).is_some()))
+                        })
+                    };
+                    let kv_obj = boa_engine::object::ObjectInitializer::new(&mut context)
+                        .function(kv_get, boa_engine::JsString::from("get"), 1)
+                        .function(kv_set, boa_engine::JsString::from("set"), 2)
+                        .function(kv_delete, boa_engine::JsString::from("delete"), 1)
+                        .build();
+                    if let Err(e) = context.register_global_property(
+                        boa_engine::JsString::from("kv"),
+                        kv_obj,
+                        boa_engine::property::Attribute::READONLY
+                    ) {
+                         return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
+                    }
+
+                    // setTimeout/clearTimeout. The sandbox is single-threaded: timers only run
+                    // after the script returns, in due order, with this blocking thread sleeping
+                    // until each one is due. The response waits for every pending callback.
+                    let timers: std::rc::Rc<std::cell::RefCell<Vec<JsTimer>>> = Default::default();
+                    let next_timer_id = std::rc::Rc::new(std::cell::Cell::new(1u32));
+                    let set_timeout = unsafe {
+                        let timers = timers.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            let callback = args.first().and_then(|v| v.as_callable()).ok_or_else(|| JsError::from_opaque(JsValue::new(boa_engine::JsString::from("setTimeout needs a function"))))?;
+                            let delay = match args.get(1) {
+                                Some(v) => v.to_number(context)?,
+                                None => 0.0,
+                            };
+                            let delay = if delay.is_finite() && delay > 0.0 { (delay as u64).min(MAX_JS_TIMER_MS) } else { 0 };
+                            let id = next_timer_id.get();
+                            next_timer_id.set(id + 1);
+                            timers.borrow_mut().push(JsTimer {
+                                id,
+                                due: Instant::now() + std::time::Duration::from_millis(delay),
+                                callback,
+                                args: args.iter().skip(2).cloned().collect(),
+                            });
+                            Ok(JsValue::new(id))
+                        })
+                    };
+                    let clear_timeout = unsafe {
+                        let timers = timers.clone();
+                        NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                            if let Some(v) = args.first() {
+                                let id = v.to_number(context)?;
+                                timers.borrow_mut().retain(|t| f64::from(t.id) != id);
+                            }
+                            Ok(JsValue::undefined())
+                        })
+                    };
+                    if let Err(e) = context.register_global_callable(boa_engine::JsString::from("setTimeout"), 2, set_timeout)
+                        .and_then(|_| context.register_global_callable(boa_engine::JsString::from("clearTimeout"), 1, clear_timeout)) {
+                         return (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response();
+                    }
+
+                    // The script starts on line JS_SCRIPT_FIRST_LINE so error positions map back to it
+                    let code = format!("(function(request) {{\n{}\n}})(request);", response_body);
+    
+                    let evaluated = context.eval(Source::from_bytes(code.as_bytes()))
+                        .and_then(|res| run_js_timers(&mut context, &timers, deadline).map(|_| res));
+                    for transaction in transactions.borrow().iter() {
+                        match transaction.rollback_if_open() {
+                            Ok(true) => println!("[DB] Rolled back a transaction left open by the script"),
+                            Ok(false) => {}
+                            Err(e) => println!("[DB] Failed to roll back transaction: {}", e),
+                        }
+                    }
+                    // Closes a streamed body once the script and its timers are done
+                    stream_tx_ref.lock().unwrap().take();
     
-                    match context.eval(Source::from_bytes(code.as_bytes())) {
+                    match evaluated {
                         Ok(res) => {
                              let final_status = *status_code_ref.lock().unwrap();
     
@@ -545,6 +2353,22 @@ async fn process_request(
                                      return (final_status, utf8).into_response();
                                  }
                              }
+                             // { binaryBase64, contentType } returns raw bytes instead of text
+                             if let Some(obj) = res.as_object() {
+                                 let get_string = |name: &str, context: &mut Context| {
+                                     obj.get(boa_engine::JsString::from(name), context).ok()
+                                         .and_then(|v| v.as_string().and_then(|s| s.to_std_string().ok()))
+                                 };
+                                 if let Some(encoded) = get_string("binaryBase64", &mut context) {
+                                     use base64::Engine;
+                                     let content_type = get_string("contentType", &mut context)
+                                         .unwrap_or_else(|| "application/octet-stream".to_string());
+                                     return match base64::engine::general_purpose::STANDARD.decode(encoded.trim()) {
+                                         Ok(bytes) => (final_status, [(axum::http::header::CONTENT_TYPE, content_type)], bytes).into_response(),
+                                         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: invalid binaryBase64: {}", e)).into_response(),
+                                     };
+                                 }
+                             }
                              if res.is_object() {
                                  let json_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("JSON"));
                                  let stringify_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("stringify"));
@@ -564,9 +2388,54 @@ async fn process_request(
                              
                              (final_status, format!("{:?}", res)).into_response()
                         },
-                        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("JS Error: {}", e)).into_response()
+                        // Whatever stopped a script past its deadline, the request timed out
+                        Err(_) if deadline.is_some_and(|deadline| Instant::now() >= deadline) => js_timeout_response(js_timeout_ms),
+                        Err(e) => {
+                            let error = js_error_json(&e);
+                            println!("[JS Error] {}", e);
+                            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+                        }
                     }
-                }).await.unwrap();
+                });
+
+                let mut result = tokio::select! {
+                    // Prefer the stream: a script that wrote and then finished must not
+                    // lose its chunks to the buffered result
+                    biased;
+                    Ok(status) = stream_start_rx => {
+                        use futures_util::{stream, StreamExt};
+                        let chunks = stream::poll_fn(move |cx| stream_rx.poll_recv(cx))
+                            .map(Ok::<_, std::convert::Infallible>);
+                        let mut response = (status, Body::from_stream(chunks)).into_response();
+                        // Server-Sent Events unless the script chose another type
+                        let headers = response.headers_mut();
+                        headers.insert(axum::http::header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+                        headers.insert(axum::http::header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+                        response.extensions_mut().insert(StreamedBody);
+                        response
+                    },
+                    result = async {
+                        if js_timeout_ms == 0 {
+                            return script.await.unwrap();
+                        }
+                        match tokio::time::timeout(std::time::Duration::from_millis(js_timeout_ms), script).await {
+                            Ok(result) => result.unwrap(),
+                            Err(_) => {
+                                println!("[JS] Mock {} timed out after {} ms", mock.id, js_timeout_ms);
+                                js_timeout_response(js_timeout_ms)
+                            }
+                        }
+                    } => result,
+                };
+
+                // Script headers replace the response's, keeping every value (e.g. several Set-Cookie)
+                let js_headers = js_headers.lock().unwrap();
+                for name in js_headers.keys() {
+                    result.headers_mut().remove(name);
+                }
+                for (name, value) in js_headers.iter() {
+                    result.headers_mut().append(name, value.clone());
+                }
                 
                 return result;
             },
@@ -578,117 +2447,2252 @@ async fn process_request(
                 // In that case, we just forward to target + path suffix (empty)
                 
                 // mock.response_body is the target URL (e.g. http://localhost:8080/api/v1/users)
-                let target_url = mock.response_body.clone();
-                println!("[PROXY] {} => {}", path, target_url);
+                let target_urls: Vec<String> = proxy_target_order(&state, &mock)
+                    .iter()
+                    .map(|target| resolve_proxy_target(target, &environment))
+                    .collect();
                 
-                // Forward request
-                let client = reqwest::Client::new();
-                let mut req_builder = client.request(method.clone(), &target_url);
-                
-                // Forward headers
-                for (k, v) in headers.iter() {
-                     if k != "host" {
-                         req_builder = req_builder.header(k, v);
-                     }
+                let client = state.proxy_client.lock().unwrap().clone();
+                let retries = state.config.lock().unwrap().proxy_retries;
+                let (response, target_url) = proxy_request(&client, &state.regex_cache, &mock, method, path, &target_urls, &headers, body, retries).await;
+                if let Some(matched) = matched.as_mut() {
+                    matched.proxy_target = Some(target_url);
                 }
-                
-                // Forward body
-                req_builder = req_builder.body(body.clone());
-                
-                match req_builder.send().await {
-                    Ok(res) => {
-                        let status = res.status();
-                        let mut response_builder = Response::builder().status(status);
-                        
-                        if let Some(headers_mut) = response_builder.headers_mut() {
-                            for (k, v) in res.headers().iter() {
-                                headers_mut.insert(k, v.clone());
-                            }
-                        }
-                        
-                        let bytes = res.bytes().await.unwrap_or_default();
-                        return response_builder.body(Body::from(bytes)).unwrap_or_else(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response());
-                    },
-                    Err(e) => {
-                        return (StatusCode::BAD_GATEWAY, format!("Proxy Error: {}", e)).into_response();
-                    }
+                return response;
+            },
+            "static" => serve_static(&response_body, "", &headers).await,
+            "grpc-web" => grpc_web_response(&headers, status_code, &response_body),
+            "json-stream" => json_stream_response(status, &response_body, mock.stream_interval_ms),
+            "file" => {
+                // response_body is the file path; Range requests get their own status
+                let mut response = serve_file(&resolve_data_path(&state, &response_body), &headers).await;
+                if response.status() == StatusCode::OK {
+                    *response.status_mut() = status;
+                }
+                response
+            },
+            "ws" => {
+                // response_body is the upstream WebSocket URL, resolved like an HTTP proxy target
+                match ws {
+                    Some(ws) => proxy_websocket(ws, resolve_proxy_target(&mock.response_body, &environment), path.to_string()).await,
+                    None => (StatusCode::BAD_REQUEST, "Expected a WebSocket upgrade request").into_response(),
+                }
+            },
+            "websocket" => {
+                // Empty response_body echoes messages back; otherwise it's the script to replay
+                match ws {
+                    Some(ws) => websocket_mock(ws, &response_body, path.to_string()),
+                    None => (StatusCode::BAD_REQUEST, "Expected a WebSocket upgrade request").into_response(),
                 }
             },
             "raw" => (status, response_body).into_response(),
+            "binary" => {
+                // response_body holds the bytes base64-encoded
+                use base64::Engine;
+                let content_type = mock.content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string());
+                match base64::engine::general_purpose::STANDARD.decode(response_body.trim()) {
+                    Ok(bytes) => (status, [(axum::http::header::CONTENT_TYPE, content_type)], bytes).into_response(),
+                    Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid base64 body: {}", e)).into_response(),
+                }
+            },
             _ => (status, response_body).into_response(),
         };
+        if let Some(i) = variant {
+            response.extensions_mut().insert(ServedVariant(i));
+        }
+        return response;
     }
     
-    // If exact match failed, try to find a proxy rule (wildcard match)
-    // We iterate over all mocks that are of type "proxy" and have a wildcard path
-    let proxy_match = {
-        let mocks = state.mocks.lock().unwrap();
-        mocks.values().find_map(|mock| {
-            if mock.response_type == "proxy" && mock.path.ends_with('*') {
-                if mock.method == "ANY" || mock.method == method.to_string() {
-                    let prefix = &mock.path[..mock.path.len() - 1];
-                    if path.starts_with(prefix) {
-                        return Some((mock.response_body.clone(), prefix.len()));
-                    }
-                }
+    // If exact match failed, try to find a proxy or static rule (wildcard match)
+    if let Some((mock, prefix_len)) = select_prefix_mock(&state, &match_request) {
+        let suffix = &path[prefix_len..];
+        *matched = Some(MatchedRoute {
+            id: mock.id.clone(),
+            response_type: mock.response_type.clone(),
+            proxy_target: None,
+            contract_violations: mock.expected_request.as_ref()
+                .map(|expected| check_expected_request(expected, &headers, uri.query(), &body))
+                .unwrap_or_default(),
+            content_type: mock.content_type.clone(),
+            cors: MockCors::for_mock(&mock, &headers),
+        });
+        if let Some(rejection) = request_schema_rejection(&mock, &headers, &body) {
+            return rejection;
+        }
+        if let Some(exhausted) = count_hit(&state, &mock, &key) {
+            return exhausted;
+        }
+        let toggle_status = toggle_status(&state, &mock).and_then(|s| StatusCode::from_u16(s).ok());
+        // A picked variant answers instead of the target or directory, e.g. an occasional 503
+        if let Some(i) = pick_weighted(&mock.weighted_responses, &state.rng) {
+            let variant = &mock.weighted_responses[i];
+            let status = toggle_status.or_else(|| StatusCode::from_u16(variant.status).ok()).unwrap_or(StatusCode::OK);
+            let mut response = typed_response(status, "json", variant.body.clone());
+            response.extensions_mut().insert(ServedVariant(i));
+            return response;
+        }
+
+        // For static mocks response_body is the root directory
+        if mock.response_type == "static" {
+            let mut response = serve_static(&mock.response_body, suffix, &headers).await;
+            if let Some(status) = toggle_status {
+                *response.status_mut() = status;
             }
-            None
-        })
-    };
+            return response;
+        }
 
-    if let Some((target_base, prefix_len)) = proxy_match {
         // Match found!
         // Construct target URL
         // mock.response_body is the target base URL, e.g. "http://localhost:8080"
         // We need to append the suffix
-        let suffix = &path[prefix_len..];
         
-        let target_base_trimmed = target_base.trim_end_matches('/');
         let suffix_trimmed = suffix.trim_start_matches('/');
+        let target_urls: Vec<String> = proxy_target_order(&state, &mock)
+            .iter()
+            .map(|target| {
+                let target_base = resolve_proxy_target(target, &environment);
+                let target_base_trimmed = target_base.trim_end_matches('/');
+                if suffix_trimmed.is_empty() {
+                     target_base_trimmed.to_string()
+                } else {
+                     format!("{}/{}", target_base_trimmed, suffix_trimmed)
+                }
+            })
+            .collect();
         
-        let target_url = if suffix_trimmed.is_empty() {
-             target_base_trimmed.to_string()
+        let client = state.proxy_client.lock().unwrap().clone();
+        let (retries, recording) = {
+            let config = state.config.lock().unwrap();
+            (config.proxy_retries, config.proxy_recording)
+        };
+        let record_method = method.clone();
+        let (response, target_url) = proxy_request(&client, &state.regex_cache, &mock, method, path, &target_urls, &headers, body, retries).await;
+        if let Some(matched) = matched.as_mut() {
+            matched.proxy_target = Some(target_url);
+        }
+        let mut response = if mock.record && recording && response.status().is_success() {
+            record_proxy_response(&state, &record_method, path, mock.port, response).await
         } else {
-             format!("{}/{}", target_base_trimmed, suffix_trimmed)
+            response
         };
-        
-        println!("[PROXY] {} => {}", path, target_url);
-        
-        // Forward request
-        let client = reqwest::Client::new();
-        let mut req_builder = client.request(method.clone(), &target_url);
-        
-        // Forward headers
-        for (k, v) in headers.iter() {
-             // Skip host header to avoid issues
-             if k != "host" {
-                 req_builder = req_builder.header(k, v);
-             }
+        if let Some(status) = toggle_status {
+            *response.status_mut() = status;
         }
-        
-        // Forward body
-        req_builder = req_builder.body(body.clone());
-        
-        match req_builder.send().await {
-            Ok(res) => {
-                let status = res.status();
-                let mut response_builder = Response::builder().status(status);
-                
-                // Forward response headers
-                if let Some(headers_mut) = response_builder.headers_mut() {
-                    for (k, v) in res.headers().iter() {
-                        headers_mut.insert(k, v.clone());
-                    }
-                }
-                
-                let bytes = res.bytes().await.unwrap_or_default();
-                return response_builder.body(Body::from(bytes)).unwrap_or_else(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response());
-            },
-            Err(e) => {
-                return (StatusCode::BAD_GATEWAY, format!("Proxy Error: {}", e)).into_response();
+        return response;
+    }
+
+    if method == Method::GET && path == "/" && state.config.lock().unwrap().show_index {
+        return index_page(&state);
+    }
+
+    let (default_type, default_status, default_body) = {
+        let config = state.config.lock().unwrap();
+        (config.default_response_type.clone(), config.default_status_code, config.default_response_body.clone())
+    };
+    if default_type == "proxy" {
+        // Catch-all proxy: the whole path and query go to the target base
+        let target_base = resolve_proxy_target(&default_body, &environment);
+        let target_url = match uri.query() {
+            Some(query) => format!("{}{}?{}", target_base.trim_end_matches('/'), path, query),
+            None => format!("{}{}", target_base.trim_end_matches('/'), path),
+        };
+        let client = state.proxy_client.lock().unwrap().clone();
+        let retries = state.config.lock().unwrap().proxy_retries;
+        let proxy = MockApi { response_type: "proxy".to_string(), response_body: default_body, ..Default::default() };
+        let (response, target_url) = proxy_request(&client, &state.regex_cache, &proxy, method, path, &[target_url], &headers, body, retries).await;
+        *matched = Some(MatchedRoute {
+            id: "default".to_string(),
+            response_type: default_type,
+            proxy_target: Some(target_url),
+            ..Default::default()
+        });
+        return response;
+    } else if !default_type.is_empty() {
+        *matched = Some(MatchedRoute {
+            id: "default".to_string(),
+            response_type: default_type.clone(),
+            ..Default::default()
+        });
+        let status = StatusCode::from_u16(default_status).unwrap_or(StatusCode::NOT_FOUND);
+        return typed_response(status, &default_type, default_body);
+    }
+
+    if let Some(allowed) = method_not_allowed(&state, &match_request) {
+        let allow = allowed.join(", ");
+        return (
+            StatusCode::METHOD_NOT_ALLOWED,
+            [(axum::http::header::ALLOW, allow)],
+            format!("Method Not Allowed: {}", key),
+        ).into_response();
+    }
+
+    (StatusCode::NOT_FOUND, format!("Not Found: {}", key)).into_response()
+}
+
+// The parts of a request that mock selection looks at
+pub struct MatchRequest<'a> {
+    method: &'a str,
+    path: &'a str,
+    host: String,
+    query: Vec<(String, String)>,
+    headers: &'a HeaderMap,
+    body_json: Option<serde_json::Value>,
+    active_group: Option<String>,
+    // Port of the server instance that received the request
+    port: u16,
+}
+
+impl<'a> MatchRequest<'a> {
+    pub fn new(state: &AppState, method: &'a str, path: &'a str, query: Option<&str>, headers: &'a HeaderMap, body: &str) -> Self {
+        let host = headers
+            .get(axum::http::header::HOST)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.rsplit_once(':').filter(|(_, port)| port.parse::<u16>().is_ok()).map_or(h, |(host, _)| host))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        MatchRequest {
+            method,
+            path,
+            host,
+            query: url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()).into_owned().collect(),
+            headers,
+            body_json: serde_json::from_str::<serde_json::Value>(body).ok(),
+            active_group: state.config.lock().unwrap().active_group.clone(),
+            port: state.serving_port,
+        }
+    }
+
+    // With an active group, mocks of other groups are ignored; ungrouped mocks always apply
+    fn group_ok(&self, mock: &MockApi) -> bool {
+        mock.group.is_none() || self.active_group.is_none() || mock.group == self.active_group
+    }
+
+    // Mocks bound to a port are only served by the instance listening on it
+    fn port_ok(&self, mock: &MockApi) -> bool {
+        mock.port.is_none_or(|port| port == self.port)
+    }
+
+    // Why a mock can't serve this request whatever its path: disabled, or left out by its
+    // group, port, host_match, query_match or header_match. None when nothing rules it out.
+    fn route_mismatch(&self, mock: &MockApi) -> Option<String> {
+        if !mock.enabled {
+            return Some("disabled".to_string());
+        }
+        if !self.group_ok(mock) {
+            return Some(format!("in group '{}', not the active group", mock.group.as_deref().unwrap_or_default()));
+        }
+        if let Some(port) = mock.port.filter(|_| !self.port_ok(mock)) {
+            return Some(format!("bound to port {}", port));
+        }
+        if let Some(h) = mock.host_match.as_ref().filter(|h| !h.eq_ignore_ascii_case(&self.host)) {
+            return Some(format!("host_match '{}' doesn't match host '{}'", h, self.host));
+        }
+        let missing_query = mock.query_match.as_ref().and_then(|wanted| {
+            wanted.iter().find(|(k, v)| !self.query.iter().any(|(qk, qv)| qk == *k && qv == *v))
+        });
+        if let Some((k, v)) = missing_query {
+            return Some(format!("query_match needs {}={}", k, v));
+        }
+        let missing_header = mock.header_match.as_ref().and_then(|wanted| {
+            wanted.iter().find(|(k, v)| !self.headers.get_all(k.as_str()).iter().any(|got| got.to_str().is_ok_and(|got| got == *v)))
+        });
+        if let Some((k, v)) = missing_header {
+            return Some(format!("header_match needs {}: {}", k, v));
+        }
+        None
+    }
+
+    // route_mismatch, plus body_match and spent single-use mocks without an exhausted_status
+    fn mismatch(&self, mock: &MockApi, counters: &HashMap<String, usize>) -> Option<String> {
+        if let Some(reason) = self.route_mismatch(mock) {
+            return Some(reason);
+        }
+        let body_ok = mock.body_match.as_ref().is_none_or(|wanted| {
+            match (serde_json::from_str::<serde_json::Value>(wanted), &self.body_json) {
+                (Ok(wanted), Some(actual)) => json_contains(actual, &wanted),
+                _ => false,
             }
+        });
+        if !body_ok {
+            return Some("body_match isn't contained in the request body".to_string());
         }
+        if mock.single_use && mock.exhausted_status.is_none() && counters.get(&mock.id).is_some_and(|hits| *hits > 0) {
+            return Some("single-use mock already used".to_string());
+        }
+        None
     }
 
-    (StatusCode::NOT_FOUND, format!("Not Found: {}", key)).into_response()
+    fn method_ok(&self, mock: &MockApi) -> bool {
+        mock.method == "ANY" || mock.method == self.method
+    }
+}
+
+// Pick the mock for a request among exact, param, regex and wildcard mocks (proxy/static
+// prefixes are select_prefix_mock's), with the captured params and the rule that matched.
+// The best mock of each kind competes on priority, ties going to the earlier kind. None when
+// a proxy/static prefix mock has a higher priority than all of them.
+// Reads state only, so match_request can dry-run it.
+fn select_mock(state: &AppState, req: &MatchRequest) -> Option<(MockApi, HashMap<String, String>, &'static str)> {
+    // Before locking the mocks, which select_prefix_mock locks too
+    let prefix_priority = select_prefix_mock(state, req).map(|(mock, _)| mock.priority);
+    let path = req.path;
+    let mocks = state.mocks.lock().unwrap();
+    let counters = state.mock_counters.lock().unwrap();
+    // Mocks ruled out for this request behave as if they didn't exist
+    let available = |mock: &&MockApi| req.mismatch(mock, &counters).is_none();
+    let mut candidates: Vec<(&MockApi, HashMap<String, String>, &'static str)> = Vec::new();
+
+    // Every kind prefers the most header constraints right after priority. Exact paths
+    // first: then prefer the specific method over ANY, then a mock for this host, then
+    // the one with the most query constraints, then the most specific body_match
+    let exact = mocks
+        .values()
+        .filter(available)
+        .filter(|mock| !matches!(mock.match_type.as_str(), "regex" | "param") && mock.path == path && req.method_ok(mock))
+        .max_by_key(|mock| (
+            mock.priority,
+            header_match_count(mock),
+            mock.method != "ANY",
+            mock.host_match.is_some(),
+            mock.query_match.as_ref().map_or(0, |q| q.len()),
+            body_match_specificity(mock),
+            std::cmp::Reverse(mock.id.as_str()),
+        ));
+    if let Some(mock) = exact {
+        candidates.push((mock, HashMap::new(), "exact"));
+    }
+
+    // Then param mocks ("/users/:id"), preferring the most literal segments
+    let param = mocks
+        .values()
+        .filter(available)
+        .filter(|mock| mock.match_type == "param" && req.method_ok(mock))
+        .filter_map(|mock| Some((mock, match_param_path(&mock.path, path)?)))
+        .max_by_key(|(mock, _)| (
+            mock.priority,
+            header_match_count(mock),
+            mock.path.split('/').filter(|s| !s.starts_with(':')).count(),
+            mock.method != "ANY",
+            body_match_specificity(mock),
+            std::cmp::Reverse(mock.id.as_str()),
+        ));
+    if let Some((mock, captures)) = param {
+        candidates.push((mock, captures, "param"));
+    }
+
+    // Regex mocks, whose path is a pattern over the whole request path
+    let mut regex_cache = state.regex_cache.lock().unwrap();
+    let regex_match = mocks
+        .values()
+        .filter(available)
+        .filter(|mock| mock.match_type == "regex" && req.method_ok(mock))
+        .filter_map(|mock| {
+            let re = match regex_cache.get(&mock.path) {
+                Some(re) => re,
+                None => {
+                    let re = compile_path_regex(&mock.path)?;
+                    regex_cache.entry(mock.path.clone()).or_insert(re)
+                }
+            };
+            Some((mock, regex_captures(re, path)?))
+        })
+        .max_by_key(|(mock, _)| (mock.priority, header_match_count(mock), std::cmp::Reverse(mock.id.as_str())));
+    if let Some((mock, captures)) = regex_match {
+        candidates.push((mock, captures, "regex"));
+    }
+
+    // Last, wildcard paths ("/static/*") of any type but proxy/static, which are
+    // handled further down. The longest prefix wins; the rest of the path is
+    // available as params.wildcard.
+    let wildcard = mocks
+        .values()
+        .filter(available)
+        .filter(|mock| mock.match_type == "exact" && !matches!(mock.response_type.as_str(), "proxy" | "static") && req.method_ok(mock))
+        .filter_map(|mock| {
+            let prefix = mock.path.strip_suffix('*')?;
+            path.starts_with(prefix).then_some((mock, prefix))
+        })
+        .max_by_key(|(mock, prefix)| (mock.priority, header_match_count(mock), prefix.len(), mock.method != "ANY", std::cmp::Reverse(mock.id.as_str())));
+    if let Some((mock, prefix)) = wildcard {
+        candidates.push((mock, HashMap::from([("wildcard".to_string(), path[prefix.len()..].to_string())]), "wildcard"));
+    }
+
+    // Keeps the first of equal priorities
+    let (mock, params, rule) = candidates.into_iter().reduce(|best, next| if next.0.priority > best.0.priority { next } else { best })?;
+    if prefix_priority.is_some_and(|priority| priority > mock.priority) {
+        return None;
+    }
+    Some((mock.clone(), params, rule))
+}
+
+// The proxy or static mock with a wildcard path ("/api/*") covering the request, preferring
+// the highest priority and then the longest prefix, with that prefix's length
+fn select_prefix_mock(state: &AppState, req: &MatchRequest) -> Option<(MockApi, usize)> {
+    let mocks = state.mocks.lock().unwrap();
+    let counters = state.mock_counters.lock().unwrap();
+    mocks.values()
+        .filter(|mock| matches!(mock.response_type.as_str(), "proxy" | "static") && req.method_ok(mock) && req.mismatch(mock, &counters).is_none())
+        .filter_map(|mock| {
+            let prefix = mock.path.strip_suffix('*')?;
+            req.path.starts_with(prefix).then_some((mock, prefix.len()))
+        })
+        .max_by_key(|(mock, prefix_len)| (mock.priority, *prefix_len, mock.method != "ANY", std::cmp::Reverse(mock.id.as_str())))
+        .map(|(mock, prefix_len)| (mock.clone(), prefix_len))
+}
+
+// The methods to list in a 405 when the path is mocked, just not for this method. Only
+// mocks that could serve the request apart from their method count, so a same-method
+// or ANY mock, or one that was skipped (host/query/header mismatch, used up), still
+// means 404. None when the request isn't a 405.
+fn method_not_allowed(state: &AppState, req: &MatchRequest) -> Option<Vec<String>> {
+    let mut allowed: Vec<String> = {
+        let mocks = state.mocks.lock().unwrap();
+        mocks.values()
+            .filter(|mock| mock_path_fits(state, mock, req.path) && req.route_mismatch(mock).is_none())
+            .map(|mock| mock.method.to_uppercase())
+            .collect()
+    };
+    if allowed.is_empty() || allowed.iter().any(|m| m == req.method || m == "ANY") {
+        return None;
+    }
+    allowed.sort();
+    allowed.dedup();
+    Some(allowed)
+}
+
+// Whether a mock's path covers the request path, ignoring every other constraint
+fn mock_path_fits(state: &AppState, mock: &MockApi, path: &str) -> bool {
+    match mock.match_type.as_str() {
+        "param" => match_param_path(&mock.path, path).is_some(),
+        "regex" => {
+            let mut regex_cache = state.regex_cache.lock().unwrap();
+            let re = match regex_cache.get(&mock.path) {
+                Some(re) => re,
+                None => match compile_path_regex(&mock.path) {
+                    Some(re) => regex_cache.entry(mock.path.clone()).or_insert(re),
+                    None => return false,
+                },
+            };
+            re.is_match(path)
+        }
+        _ => mock.path == path || mock.path.strip_suffix('*').is_some_and(|prefix| path.starts_with(prefix)),
+    }
+}
+
+fn maintenance_allows(maintenance: &Maintenance, path: &str) -> bool {
+    maintenance.allow_paths.iter().any(|allowed| match allowed.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => path == allowed,
+    })
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchExplanation {
+    // Mock that would serve the request
+    pub mock_id: Option<String>,
+    // exact, param, regex, wildcard or prefix for a mock; otherwise health, maintenance,
+    // quota, index, default, method-not-allowed or not-found
+    pub rule: String,
+    pub reason: String,
+    pub params: HashMap<String, String>,
+    // Mocks whose path fits the request but that weren't chosen, and why
+    pub near_misses: Vec<NearMiss>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NearMiss {
+    pub id: String,
+    pub reason: String,
+}
+
+// Dry run of process_request's routing: which mock would answer and why, without
+// counting hits, advancing sequences or recording anything
+pub fn explain_match(state: &AppState, req: &MatchRequest) -> MatchExplanation {
+    let path = req.path;
+    let explanation = |rule: &str, reason: String| MatchExplanation {
+        mock_id: None,
+        rule: rule.to_string(),
+        reason,
+        params: HashMap::new(),
+        near_misses: Vec::new(),
+    };
+
+    let (health_path, maintenance, global_quota, chaos_failure_rate, show_index, default_type) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.health_path.clone(),
+            config.maintenance.clone(),
+            config.global_quota.clone(),
+            config.chaos_failure_rate,
+            config.show_index,
+            config.default_response_type.clone(),
+        )
+    };
+    if !health_path.is_empty() && path == health_path {
+        return explanation("health", format!("{} is the health-check path", path));
+    }
+    if maintenance.is_some_and(|m| !maintenance_allows(&m, path)) {
+        return explanation("maintenance", "maintenance mode is on and the path isn't in allow_paths".to_string());
+    }
+    // A real request is counted before the quota check, so it's refused once the count
+    // has reached the limit
+    if let Some(quota) = global_quota.filter(|q| *state.request_count.lock().unwrap() >= q.limit) {
+        return explanation("quota", format!("the global quota of {} requests is used up, so {} is returned until it's reset", quota.limit, quota.status));
+    }
+
+    let mut result = if let Some((mock, params, rule)) = select_mock(state, req) {
+        MatchExplanation {
+            reason: format!("{} is the best {} match for {} {}", mock.id, rule, req.method, path),
+            mock_id: Some(mock.id),
+            rule: rule.to_string(),
+            params,
+            near_misses: Vec::new(),
+        }
+    } else if let Some((mock, prefix_len)) = select_prefix_mock(state, req) {
+        MatchExplanation {
+            reason: format!("{} is the longest {} prefix covering {}", mock.id, mock.response_type, path),
+            mock_id: Some(mock.id),
+            rule: "prefix".to_string(),
+            params: HashMap::from([("wildcard".to_string(), path[prefix_len..].to_string())]),
+            near_misses: Vec::new(),
+        }
+    } else if req.method == "GET" && path == "/" && show_index {
+        explanation("index", "no mock for / and the index page is enabled".to_string())
+    } else if !default_type.is_empty() {
+        explanation("default", format!("no mock matches, so the default {} response is served", default_type))
+    } else {
+        if let Some(allowed) = method_not_allowed(state, req) {
+            explanation("method-not-allowed", format!("{} is only mocked for {}", path, allowed.join(", ")))
+        } else {
+            explanation("not-found", format!("no mock matches {} {}", req.method, path))
+        }
+    };
+
+    let mocks = state.mocks.lock().unwrap();
+    let counters = state.mock_counters.lock().unwrap();
+    for mock in mocks.values() {
+        if result.mock_id.as_ref() == Some(&mock.id) {
+            continue;
+        }
+        if !mock_path_fits(state, mock, path) {
+            continue;
+        }
+        let reason = if !req.method_ok(mock) {
+            format!("method is {}", mock.method)
+        } else if let Some(reason) = req.mismatch(mock, &counters) {
+            reason
+        } else if let Some(id) = &result.mock_id {
+            format!("lower precedence than {}", id)
+        } else {
+            "not selected".to_string()
+        };
+        result.near_misses.push(NearMiss { id: mock.id.clone(), reason });
+    }
+    result.near_misses.sort_by(|a, b| a.id.cmp(&b.id));
+    if chaos_failure_rate > 0.0 {
+        result.reason.push_str(&format!(" (chaos mode fails {}% of requests with a 500 first)", chaos_failure_rate * 100.0));
+    }
+    result
+}
+
+// Whether `expected` is contained in `actual`: object keys may be a subset at any depth,
+// each expected array element must match some actual element, scalars must be equal
+fn json_contains(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter()
+            .all(|(k, v)| actual.get(k).is_some_and(|a| json_contains(a, v))),
+        (Value::Array(actual), Value::Array(expected)) => expected.iter()
+            .all(|e| actual.iter().any(|a| json_contains(a, e))),
+        _ => actual == expected,
+    }
+}
+
+// Number of headers a mock's header_match requires; 0 without one
+fn header_match_count(mock: &MockApi) -> usize {
+    mock.header_match.as_ref().map_or(0, |h| h.len())
+}
+
+// Number of leaf values constrained by a mock's body_match; 0 without one
+fn body_match_specificity(mock: &MockApi) -> usize {
+    fn leaves(value: &serde_json::Value) -> usize {
+        match value {
+            serde_json::Value::Object(map) => map.values().map(leaves).sum(),
+            serde_json::Value::Array(items) => items.iter().map(leaves).sum(),
+            _ => 1,
+        }
+    }
+    mock.body_match.as_deref()
+        .and_then(|b| serde_json::from_str::<serde_json::Value>(b).ok())
+        .map_or(0, |v| leaves(&v))
+}
+
+// Returns the current step of a "sequence" mock and advances it, wrapping after the last
+fn next_sequence_step(state: &AppState, mock: &MockApi) -> Result<SequenceStep, String> {
+    let steps: Vec<SequenceStep> = serde_json::from_str(&mock.response_body)
+        .map_err(|e| format!("Invalid sequence for {}: {}", mock.id, e))?;
+    if steps.is_empty() {
+        return Err(format!("Empty sequence for {}", mock.id));
+    }
+    let mut positions = state.sequence_positions.lock().unwrap();
+    let position = positions.entry(mock.id.clone()).or_insert(0);
+    let index = *position % steps.len();
+    *position = (index + 1) % steps.len();
+    Ok(steps[index].clone())
+}
+
+fn log_headers(headers: &HeaderMap, include_sensitive: bool) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if !include_sensitive && (name == axum::http::header::AUTHORIZATION || name == axum::http::header::PROXY_AUTHORIZATION) {
+                "***".to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+// Body with the content type of a "json", "html" or "raw" response type; JSON that doesn't
+// parse is sent as text
+fn typed_response(status: StatusCode, response_type: &str, body: String) -> Response {
+    match response_type {
+        "json" => match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(json) => (status, Json(json)).into_response(),
+            Err(_) => (status, body).into_response(),
+        },
+        "html" => (status, Html(body)).into_response(),
+        _ => (status, body).into_response(),
+    }
+}
+
+// Built-in landing page listing the registered mocks
+fn index_page(state: &AppState) -> Response {
+    let mut mocks: Vec<MockApi> = state.mocks.lock().unwrap().values().cloned().collect();
+    mocks.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.method.cmp(&b.method)));
+
+    let rows: String = mocks
+        .iter()
+        .map(|mock| {
+            format!(
+                "<tr{}><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+                if mock.enabled { "" } else { " style=\"opacity:0.5\"" },
+                escape_html(&mock.method),
+                escape_html(&mock.path),
+                escape_html(&mock.response_type),
+            )
+        })
+        .collect();
+
+    Html(format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Evo API Mocker</title></head><body>\n\
+         <h1>Evo API Mocker</h1>\n<p>{} mock(s) registered.</p>\n\
+         <table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n\
+         <tr><th>Method</th><th>Path</th><th>Type</th></tr>\n{}</table>\n</body></html>\n",
+        mocks.len(),
+        rows,
+    ))
+    .into_response()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Stream a JSON array element by element so clients can parse it progressively; the
+// output as a whole is still one valid JSON document.
+fn json_stream_response(status: StatusCode, body: &str, interval_ms: Option<u64>) -> Response {
+    use futures_util::{stream, StreamExt};
+
+    let elements: Vec<serde_json::Value> = match serde_json::from_str(body) {
+        Ok(elements) => elements,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid json-stream body: {}", e)).into_response(),
+    };
+    let interval = interval_ms.map(std::time::Duration::from_millis);
+
+    let items = stream::iter(elements.into_iter().enumerate()).then(move |(i, element)| async move {
+        if i == 0 {
+            return element.to_string();
+        }
+        if let Some(interval) = interval {
+            tokio::time::sleep(interval).await;
+        }
+        format!(",{}", element)
+    });
+    let chunks = stream::once(async { "[".to_string() })
+        .chain(items)
+        .chain(stream::once(async { "]".to_string() }))
+        .map(Ok::<_, std::convert::Infallible>);
+
+    let mut response = (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(chunks),
+    ).into_response();
+    response.extensions_mut().insert(StreamedBody);
+    response
+}
+
+// gRPC-Web always answers HTTP 200; the outcome travels in a trailer frame. A 2xx mock
+// sends response_body as a single message: raw JSON for `+json` clients, otherwise the
+// body must be the base64 of the encoded protobuf message. Other status codes are mapped
+// to the equivalent grpc-status with the body as grpc-message.
+fn grpc_web_response(request_headers: &HeaderMap, status_code: u16, body: &str) -> Response {
+    use base64::Engine;
+
+    let request_type = request_headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/grpc-web+proto")
+        .to_ascii_lowercase();
+    let text_mode = request_type.starts_with("application/grpc-web-text");
+    let json_mode = request_type.ends_with("+json");
+
+    let mut framed = Vec::new();
+    let (grpc_status, grpc_message) = if (200..300).contains(&status_code) {
+        let message = if json_mode {
+            Ok(body.as_bytes().to_vec())
+        } else {
+            base64::engine::general_purpose::STANDARD.decode(body.trim())
+        };
+        match message {
+            Ok(message) => {
+                framed.push(0x00);
+                framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+                framed.extend_from_slice(&message);
+                (0, String::new())
+            }
+            Err(e) => (13, format!("Mock body is not valid base64 protobuf: {}", e)),
+        }
+    } else {
+        (grpc_status_for_http(status_code), body.to_string())
+    };
+
+    let mut trailers = format!("grpc-status:{}\r\n", grpc_status);
+    if !grpc_message.is_empty() {
+        trailers.push_str(&format!("grpc-message:{}\r\n", percent_encode_grpc_message(&grpc_message)));
+    }
+    framed.push(0x80);
+    framed.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+    framed.extend_from_slice(trailers.as_bytes());
+
+    let (content_type, payload) = if text_mode {
+        ("application/grpc-web-text", base64::engine::general_purpose::STANDARD.encode(&framed).into_bytes())
+    } else if json_mode {
+        ("application/grpc-web+json", framed)
+    } else {
+        ("application/grpc-web+proto", framed)
+    };
+    ([(axum::http::header::CONTENT_TYPE, content_type)], payload).into_response()
+}
+
+// HTTP to gRPC status mapping from the gRPC HTTP/2 spec, plus a few common extras
+fn grpc_status_for_http(status_code: u16) -> u8 {
+    match status_code {
+        400 => 3,  // INVALID_ARGUMENT
+        401 => 16, // UNAUTHENTICATED
+        403 => 7,  // PERMISSION_DENIED
+        404 => 5,  // NOT_FOUND
+        409 => 6,  // ALREADY_EXISTS
+        429 => 8,  // RESOURCE_EXHAUSTED
+        499 => 1,  // CANCELLED
+        501 => 12, // UNIMPLEMENTED
+        502..=503 => 14, // UNAVAILABLE
+        504 => 4,  // DEADLINE_EXCEEDED
+        _ => 2,    // UNKNOWN
+    }
+}
+
+fn percent_encode_grpc_message(message: &str) -> String {
+    message
+        .bytes()
+        .map(|b| match b {
+            b' '..=b'~' if b != b'%' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// Replace {{var.NAME}} with the environment's value; unknown names are left as-is
+fn resolve_vars(text: &str, variables: &HashMap<String, String>) -> String {
+    if !text.contains("{{var.") {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{var.") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + "{{var.".len()..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                match variables.get(name) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[start..start + "{{var.".len() + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+struct TemplateContext<'a> {
+    method: &'a str,
+    path: &'a str,
+    query: &'a [(String, String)],
+    headers: &'a HeaderMap,
+    params: &'a HashMap<String, String>,
+    now_ms: i64,
+    rng: &'a Mutex<StdRng>,
+}
+
+// Expands {{uuid}}, {{now}} (epoch millis), {{timestamp}} (epoch seconds), {{randomInt min max}},
+// {{request.method}}, {{request.path}} and {{request.query|headers|params.name}}. Unknown
+// tokens are left as-is.
+fn render_template(text: &str, ctx: &TemplateContext) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        match render_token(after[..end].trim(), ctx) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn render_token(token: &str, ctx: &TemplateContext) -> Option<String> {
+    let mut words = token.split_whitespace();
+    match words.next()? {
+        "uuid" => Some(uuid::Uuid::new_v4().to_string()),
+        "now" => Some(ctx.now_ms.to_string()),
+        "timestamp" => Some((ctx.now_ms / 1000).to_string()),
+        "randomInt" => {
+            let min: i64 = words.next()?.parse().ok()?;
+            let max: i64 = words.next()?.parse().ok()?;
+            if min > max {
+                return None;
+            }
+            Some(ctx.rng.lock().unwrap().random_range(min..=max).to_string())
+        }
+        "request.method" => Some(ctx.method.to_string()),
+        "request.path" => Some(ctx.path.to_string()),
+        name => {
+            if let Some(key) = name.strip_prefix("request.query.") {
+                ctx.query.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            } else if let Some(key) = name.strip_prefix("request.headers.") {
+                ctx.headers.get(key).and_then(|v| v.to_str().ok()).map(str::to_string)
+            } else if let Some(key) = name.strip_prefix("request.params.") {
+                ctx.params.get(key).cloned()
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// Convert a result row to a JSON object keyed by column name
+fn sql_row_to_json(row: &sqlx::any::AnyRow) -> serde_json::Value {
+    use sqlx::{Row, Column, TypeInfo};
+    let mut row_obj = serde_json::Map::new();
+    for col in row.columns() {
+        let name = col.name();
+        // Option probes: a NULL decodes as None whatever the column type, so real NULLs
+        // become null and only values that fail every decode reach the unsupported case
+        let val_json = if let Ok(v) = row.try_get::<Option<String>, _>(name) {
+            serde_json::Value::from(v)
+        } else if let Ok(v) = row.try_get::<Option<i64>, _>(name) {
+            serde_json::Value::from(v)
+        } else if let Ok(v) = row.try_get::<Option<f64>, _>(name) {
+            // NaN and infinities have no JSON form and stay null
+            serde_json::Value::from(v)
+        } else if let Ok(v) = row.try_get::<Option<bool>, _>(name) {
+            serde_json::Value::from(v)
+        // Any driver doesn't support i8 directly, map to i16 or i32
+        } else if let Ok(v) = row.try_get::<Option<i16>, _>(name) {
+            serde_json::Value::from(v)
+        } else if let Ok(v) = row.try_get::<Option<i32>, _>(name) {
+            serde_json::Value::from(v)
+        } else {
+            let type_name = col.type_info().name();
+            println!("[DB] Column '{}' has unsupported type {}", name, type_name);
+            serde_json::Value::String(format!("[unsupported type {}]", type_name))
+        };
+        row_obj.insert(name.to_string(), val_json);
+    }
+    serde_json::Value::Object(row_obj)
+}
+
+// A transaction opened by db.begin(). Its connection is driven by the runtime that
+// opened it, so the runtime lives as long as the transaction.
+struct JsTransaction {
+    rt: tokio::runtime::Runtime,
+    tx: std::cell::RefCell<Option<sqlx::Transaction<'static, Any>>>,
+}
+
+impl JsTransaction {
+    // Roll back unless already committed or rolled back; used when the script ends
+    fn rollback_if_open(&self) -> Result<bool, String> {
+        match self.tx.borrow_mut().take() {
+            Some(tx) => self.rt.block_on(tx.rollback()).map(|_| true).map_err(|e| e.to_string()),
+            None => Ok(false),
+        }
+    }
+}
+
+// The Any driver only carries bool, integer, float, text and blob values, so
+// DATE/DATETIME/JSON/unsigned columns are rejected while the rows are fetched,
+// before any try_get runs. Point the script author at the SQL-side workaround.
+fn any_driver_error_hint(error: String) -> String {
+    if error.contains("Any driver does not support") {
+        format!("{} (cast the column in SQL, e.g. CAST(created_at AS CHAR) or CAST(id AS SIGNED))", error)
+    } else {
+        error
+    }
+}
+
+// The console object of js mocks and hook scripts. Every level is printed and sent to the
+// frontend as a "js-console" event, with `source` (e.g. ("mock_id", id)) naming the script.
+fn js_console_object(context: &mut Context, app_handle: Option<tauri::AppHandle>, source: (&'static str, String)) -> boa_engine::JsObject {
+    use boa_engine::{JsResult, JsValue, NativeFunction};
+    let mut console_init = boa_engine::object::ObjectInitializer::new(context);
+    for level in ["log", "info", "warn", "error"] {
+        let app_handle = app_handle.clone();
+        let source = source.clone();
+        let console_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, context| -> JsResult<JsValue> {
+                let message = js_console_text(args, context);
+                println!("[JS Console] [{}] {}", level, message);
+                if let Some(app_handle) = app_handle.as_ref() {
+                    use tauri::Emitter;
+                    let mut payload = serde_json::json!({ "level": level, "message": message });
+                    payload[source.0] = serde_json::Value::String(source.1.clone());
+                    let _ = app_handle.emit("js-console", payload);
+                }
+                Ok(JsValue::undefined())
+            })
+        };
+        console_init.function(console_fn, boa_engine::JsString::from(level), 0);
+    }
+    console_init.build()
+}
+
+// console.* arguments joined by spaces; strings as-is, anything else through JSON.stringify
+fn js_console_text(args: &[boa_engine::JsValue], context: &mut Context) -> String {
+    let mut output = String::new();
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            output.push(' ');
+        }
+
+        if arg.is_string() {
+            output.push_str(&arg.as_string().unwrap().to_std_string().unwrap());
+        } else {
+            let json_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("JSON"));
+            let stringify_key = boa_engine::property::PropertyKey::from(boa_engine::JsString::from("stringify"));
+
+            if let Ok(json_obj) = context.global_object().get(json_key, context) {
+                if let Some(json_obj) = json_obj.as_object() {
+                     if let Ok(stringify) = json_obj.get(stringify_key, context) {
+                         if let Ok(s) = stringify.as_callable().unwrap().call(&boa_engine::JsValue::from(json_obj.clone()), std::slice::from_ref(arg), context) {
+                             if let Some(str_val) = s.as_string() {
+                                 if let Ok(utf8) = str_val.to_std_string() {
+                                     output.push_str(&utf8);
+                                     continue;
+                                 }
+                             }
+                         }
+                     }
+                }
+            }
+            output.push_str(&format!("{:?}", arg));
+        }
+    }
+    output
+}
+
+// Line of the wrapped js mock code where the mock's own script starts
+const JS_SCRIPT_FIRST_LINE: u32 = 2;
+
+// A js mock's uncaught error as {error, line, column, stack}. Positions are relative to the
+// mock's script and null when boa reports none.
+fn js_error_json(e: &boa_engine::JsError) -> serde_json::Value {
+    // Script line/column from a wrapped-code position; None inside the wrapper itself
+    let script_position = |line: u32, column: u32| {
+        (line >= JS_SCRIPT_FIRST_LINE).then(|| (line - JS_SCRIPT_FIRST_LINE + 1, column))
+    };
+    // Script code positions end in "(<path> at :line:column)"; native ones in "(native ...)".
+    // Returns where the ":line:column" suffix starts, and the position it names.
+    let position_suffix = |text: &str| {
+        if text.contains("(native") {
+            return None;
+        }
+        let mut parts = text.strip_suffix(')')?.rsplitn(3, ':');
+        let column = parts.next()?.parse::<u32>().ok()?;
+        let line = parts.next()?.parse::<u32>().ok()?;
+        Some((parts.next()?.len(), script_position(line, column)?))
+    };
+
+    // Display is the message, with the throw position for Error objects, followed by one
+    // "    at <frame>" line per stack frame
+    let text = e.to_string();
+    let mut lines = text.lines();
+    let mut error = lines.next().unwrap_or_default().to_string();
+    let mut position = None;
+    if let Some((start, at)) = error.rfind(" (").and_then(|start| Some((start, position_suffix(&error[start..])?.1))) {
+        error.truncate(start);
+        position = Some(at);
+    }
+    let stack: Vec<String> = lines
+        .map(|line| line.trim().trim_start_matches("at ").to_string())
+        // <main> is the wrapper calling the script
+        .filter(|frame| !frame.starts_with("<main>"))
+        .map(|frame| match position_suffix(&frame) {
+            Some((prefix_len, (line, column))) => {
+                position.get_or_insert((line, column));
+                format!("{}:{}:{})", &frame[..prefix_len], line, column)
+            }
+            None => frame,
+        })
+        .collect();
+
+    // Syntax errors carry their position in the message instead: "... at line 3, col 7"
+    if position.is_none() {
+        position = error.rsplit_once(" at line ").and_then(|(_, rest)| {
+            let (line, column) = rest.split_once(", col ")?;
+            script_position(line.trim().parse().ok()?, column.trim().parse().ok()?)
+        });
+    }
+
+    serde_json::json!({
+        "error": error,
+        "line": position.map(|(line, _)| line),
+        "column": position.map(|(_, column)| column),
+        "stack": stack,
+    })
+}
+
+struct JsTimer {
+    id: u32,
+    due: Instant,
+    callback: boa_engine::JsObject,
+    args: Vec<boa_engine::JsValue>,
+}
+
+fn js_timeout_response(js_timeout_ms: u64) -> Response {
+    (
+        StatusCode::GATEWAY_TIMEOUT,
+        Json(serde_json::json!({ "error": format!("Script exceeded js_timeout_ms ({} ms)", js_timeout_ms) })),
+    ).into_response()
+}
+
+// Runs promise jobs and then every pending setTimeout callback in due order, sleeping
+// until each is due. Callbacks may schedule further timers. A timer due after `deadline`
+// fails the script once the deadline passes.
+fn run_js_timers(context: &mut Context, timers: &std::cell::RefCell<Vec<JsTimer>>, deadline: Option<Instant>) -> boa_engine::JsResult<()> {
+    context.run_jobs()?;
+    loop {
+        let next = {
+            let mut timers = timers.borrow_mut();
+            let Some(index) = (0..timers.len()).min_by_key(|&i| (timers[i].due, timers[i].id)) else {
+                break;
+            };
+            timers.remove(index)
+        };
+        if let Some(deadline) = deadline.filter(|deadline| next.due > *deadline) {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            return Err(boa_engine::JsNativeError::runtime_limit().with_message("Script timed out waiting for a timer").into());
+        }
+        let wait = next.due.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+        next.callback.call(&boa_engine::JsValue::undefined(), &next.args, context)?;
+        context.run_jobs()?;
+    }
+    Ok(())
+}
+
+// Reads the optional params array passed to db.query / db.execute.
+fn js_sql_params(value: Option<&boa_engine::JsValue>, context: &mut boa_engine::Context) -> boa_engine::JsResult<Vec<serde_json::Value>> {
+    let value = match value {
+        Some(value) if !value.is_null_or_undefined() => value,
+        _ => return Ok(Vec::new()),
+    };
+    match value.to_json(context)? {
+        Some(serde_json::Value::Array(params)) => Ok(params),
+        _ => Err(boa_engine::JsError::from_opaque(boa_engine::JsValue::new(boa_engine::JsString::from("SQL params must be an array")))),
+    }
+}
+
+// Binds params in order, so they line up with the driver's `?` / `$1` placeholders.
+fn bind_sql_params<'q>(
+    mut query: sqlx::query::Query<'q, Any, sqlx::any::AnyArguments<'q>>,
+    params: &[serde_json::Value],
+) -> Result<sqlx::query::Query<'q, Any, sqlx::any::AnyArguments<'q>>, String> {
+    for (index, param) in params.iter().enumerate() {
+        query = match param {
+            serde_json::Value::Null => query.bind(Option::<String>::None),
+            serde_json::Value::Bool(b) => query.bind(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => query.bind(s.clone()),
+            _ => return Err(format!("Unsupported SQL param at index {}: expected string, number, boolean or null", index)),
+        };
+    }
+    Ok(query)
+}
+
+fn resolve_proxy_target(target: &str, environment: &Environment) -> String {
+    let target = resolve_vars(target, &environment.variables);
+    match environment.proxy_base_url.as_deref() {
+        Some(base) if target.starts_with('/') => format!("{}{}", base.trim_end_matches('/'), target),
+        _ => target,
+    }
+}
+
+// Proxy targets in the order to try them, rotated by the balancing strategy
+fn proxy_target_order(state: &AppState, mock: &MockApi) -> Vec<String> {
+    let n = mock.proxy_targets.len();
+    if n == 0 {
+        return vec![mock.response_body.clone()];
+    }
+    let start = if mock.proxy_balance == "random" {
+        state.rng.lock().unwrap().random_range(0..n)
+    } else {
+        let mut rotation = state.proxy_rotation.lock().unwrap();
+        let next = rotation.entry(mock.id.clone()).or_insert(0);
+        let start = *next % n;
+        *next = next.wrapping_add(1);
+        start
+    };
+    (0..n).map(|i| mock.proxy_targets[(start + i) % n].clone()).collect()
+}
+
+// Forward to the first target URL, or with proxy_failover to the next one while targets
+// refuse the connection. Returns the response and the target that produced it.
+#[allow(clippy::too_many_arguments)]
+async fn proxy_request(client: &reqwest::Client, regex_cache: &Mutex<HashMap<String, regex::Regex>>, mock: &MockApi, method: Method, path: &str, target_urls: &[String], headers: &HeaderMap, body: String, retries: u32) -> (Response, String) {
+    let attempts = if mock.proxy_failover { target_urls.len() } else { 1 };
+    // Only requests that are safe to send twice are retried
+    let retries = if method.is_idempotent() { retries } else { 0 };
+    let mut last_error = None;
+    for target_url in target_urls.iter().take(attempts) {
+        let target_url = &rewrite_proxy_url(regex_cache, mock, target_url);
+        let mut retried = 0;
+        loop {
+            println!("[PROXY] {} => {}", path, target_url);
+            match forward_request(client, mock, method.clone(), target_url, headers, body.clone()).await {
+                Ok(response) => return (response, target_url.clone()),
+                Err(e) if e.is_connect() && retried < retries => {
+                    retried += 1;
+                    println!("[PROXY] {} unavailable, retry {}/{}: {}", target_url, retried, retries, e);
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    println!("[PROXY] {} unavailable: {}", target_url, e);
+                    last_error = Some((e, target_url.clone()));
+                    break;
+                }
+                Err(e) => return ((StatusCode::BAD_GATEWAY, format!("Proxy Error: {}", e)).into_response(), target_url.clone()),
+            }
+        }
+    }
+    match last_error {
+        Some((e, target_url)) => ((StatusCode::BAD_GATEWAY, format!("Proxy Error: {}", e)).into_response(), target_url),
+        None => ((StatusCode::BAD_GATEWAY, "Proxy Error: no target configured").into_response(), String::new()),
+    }
+}
+
+// Buffers a proxied response and stores it as an exact json/html/raw mock for this
+// method and path, which then matches before the wildcard proxy. Existing proxy mocks
+// on that route are never overwritten; re-recording over a disabled mock enables it.
+async fn record_proxy_response(state: &AppState, method: &Method, path: &str, port: Option<u16>, response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => return (StatusCode::BAD_GATEWAY, format!("Proxy Error: {}", e)).into_response(),
+    };
+    // The body is buffered now, so the logger can capture it
+    parts.extensions.remove::<StreamedBody>();
+
+    let content_type = parts.headers.get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(text) => {
+            let response_type = if content_type.contains("json") && serde_json::from_str::<serde_json::Value>(&text).is_ok() {
+                "json"
+            } else if content_type.contains("html") {
+                "html"
+            } else {
+                "raw"
+            };
+            let key = mock_key(method.as_str(), path, None, None, None, None, port);
+            let snapshot = {
+                let mut mocks = state.mocks.lock().unwrap();
+                let previous = mocks.get(&key).cloned();
+                if previous.as_ref().is_some_and(|m| m.response_type == "proxy") {
+                    println!("[PROXY] Not recording {}: a proxy mock owns that route", key);
+                    None
+                } else {
+                    let recorded = MockApi {
+                        id: key.clone(),
+                        path: path.to_string(),
+                        method: method.to_string(),
+                        response_body: text,
+                        status_code: parts.status.as_u16(),
+                        response_type: response_type.to_string(),
+                        port,
+                        // Otherwise the recording would never be replayed
+                        enabled: true,
+                        ..previous.unwrap_or_default()
+                    };
+                    mocks.insert(key.clone(), recorded);
+                    println!("[PROXY] Recorded {}", key);
+                    Some(mocks.clone())
+                }
+            };
+            // Saved after releasing the lock, so the file write doesn't hold up other requests
+            if let Some(mocks) = snapshot {
+                if let Some(app_handle) = state.app_handle.lock().unwrap().clone() {
+                    if let Err(e) = crate::save_mocks(&app_handle, &mocks) {
+                        println!("[PROXY] Failed to save recorded mock {}: {}", key, e);
+                    }
+                    use tauri::Emitter;
+                    let _ = app_handle.emit("mock-recorded", &key);
+                }
+            }
+        }
+        Err(_) => println!("[PROXY] Not recording {} {}: binary response", method, path),
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+// Applies the mock's proxy_rewrite to the path of target_url, leaving host and query alone.
+// An invalid pattern or URL leaves the URL unchanged.
+fn rewrite_proxy_url(regex_cache: &Mutex<HashMap<String, regex::Regex>>, mock: &MockApi, target_url: &str) -> String {
+    let Some((find, replace)) = &mock.proxy_rewrite else {
+        return target_url.to_string();
+    };
+    let key = format!("rewrite:{}", find);
+    let cached = regex_cache.lock().unwrap().get(&key).cloned();
+    let re = match cached {
+        Some(re) => re,
+        None => match regex::Regex::new(find) {
+            Ok(re) => regex_cache.lock().unwrap().entry(key).or_insert(re).clone(),
+            Err(e) => {
+                println!("[PROXY] Ignoring invalid proxy_rewrite pattern for {}: {}", mock.id, e);
+                return target_url.to_string();
+            }
+        },
+    };
+    match url::Url::parse(target_url) {
+        Ok(mut url) => {
+            let path = re.replace(url.path(), replace.as_str()).into_owned();
+            url.set_path(&path);
+            url.to_string()
+        }
+        Err(e) => {
+            println!("[PROXY] Can't rewrite {}: {}", target_url, e);
+            target_url.to_string()
+        }
+    }
+}
+
+// Forward the request to `target_url` and relay the upstream response
+async fn forward_request(client: &reqwest::Client, mock: &MockApi, method: Method, target_url: &str, headers: &HeaderMap, body: String) -> Result<Response, reqwest::Error> {
+    let mut req_builder = client.request(method, target_url);
+    
+    // Forward headers
+    for (k, v) in headers.iter() {
+         // Skip host header to avoid issues; reqwest derives it from the target URL
+         if k != "host" {
+             req_builder = req_builder.header(k, v);
+         }
+    }
+
+    // Virtual-hosted upstreams may need the client's Host or a specific one
+    match mock.proxy_host.as_deref() {
+        Some("preserve") => {
+            if let Some(host) = headers.get(axum::http::header::HOST) {
+                req_builder = req_builder.header(axum::http::header::HOST, host);
+            }
+        }
+        Some(host) if !host.is_empty() => {
+            req_builder = req_builder.header(axum::http::header::HOST, host);
+        }
+        _ => {}
+    }
+    
+    // Forward body
+    req_builder = req_builder.body(body);
+    
+    let res = req_builder.send().await?;
+    let status = res.status();
+    let mut response_builder = Response::builder().status(status);
+    
+    // Forward response headers
+    if let Some(headers_mut) = response_builder.headers_mut() {
+        for (k, v) in res.headers().iter() {
+            headers_mut.insert(k, v.clone());
+        }
+        for name in &mock.proxy_remove_headers {
+            if let Ok(name) = axum::http::HeaderName::from_bytes(name.trim().as_bytes()) {
+                headers_mut.remove(name);
+            }
+        }
+        for (name, value) in &mock.proxy_add_headers {
+            if let (Ok(name), Ok(value)) = (axum::http::HeaderName::from_bytes(name.trim().as_bytes()), HeaderValue::from_str(value)) {
+                headers_mut.insert(name, value);
+            }
+        }
+    }
+    
+    // Stream the upstream body through instead of loading it into memory
+    response_builder = response_builder.extension(StreamedBody);
+    Ok(response_builder.body(Body::from_stream(res.bytes_stream())).unwrap_or_else(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response").into_response()))
+}
+
+// Compare a request against a mock's expected_request, describing every mismatch
+fn check_expected_request(expected: &ExpectedRequest, headers: &HeaderMap, query: Option<&str>, body: &str) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    for (name, want) in &expected.headers {
+        match headers.get(name.as_str()).map(|v| v.to_str().unwrap_or_default()) {
+            None => violations.push(format!("missing header '{}'", name)),
+            Some(got) if !want.is_empty() && got != want => {
+                violations.push(format!("header '{}': expected '{}', got '{}'", name, want, got));
+            }
+            _ => {}
+        }
+    }
+
+    let query: HashMap<String, String> = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect();
+    for (name, want) in &expected.query {
+        match query.get(name) {
+            None => violations.push(format!("missing query parameter '{}'", name)),
+            Some(got) if !want.is_empty() && got != want => {
+                violations.push(format!("query parameter '{}': expected '{}', got '{}'", name, want, got));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(schema) = &expected.body_schema {
+        match (jsonschema::validator_for(schema), serde_json::from_str::<serde_json::Value>(body)) {
+            (Err(e), _) => violations.push(format!("invalid body_schema: {}", e)),
+            (_, Err(e)) => violations.push(format!("body is not valid JSON: {}", e)),
+            (Ok(validator), Ok(instance)) => {
+                for error in validator.iter_errors(&instance) {
+                    violations.push(format!("body {}: {}", error.instance_path, error));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+// Validate a JSON request body against a mock's request_schema. Requests without a JSON
+// Content-Type are not checked.
+// The 400 for a body that fails the mock's request_schema. Checked before count_hit, so a
+// bad request doesn't use up a single-use mock.
+fn request_schema_rejection(mock: &MockApi, headers: &HeaderMap, body: &str) -> Option<Response> {
+    let errors = check_request_schema(mock.request_schema.as_deref()?, headers, body);
+    if errors.is_empty() {
+        return None;
+    }
+    Some((
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": "Request body does not match request_schema", "details": errors })),
+    ).into_response())
+}
+
+// Count a hit on the mock. For a single-use mock that was already used, the response to send
+// instead: exhausted_status, or a 404 after losing a race for the single use.
+fn count_hit(state: &AppState, mock: &MockApi, key: &str) -> Option<Response> {
+    let hits = {
+        let mut counters = state.mock_counters.lock().unwrap();
+        let hits = counters.entry(mock.id.clone()).or_insert(0);
+        *hits += 1;
+        *hits
+    };
+    if !mock.single_use || hits == 1 {
+        return None;
+    }
+    Some(match mock.exhausted_status.and_then(|s| StatusCode::from_u16(s).ok()) {
+        Some(status) => (status, format!("Mock already used: {}", mock.id)).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("Not Found: {}", key)).into_response(),
+    })
+}
+
+// The status picked by the mock's toggle_ref; toggles that were never set count as off
+fn toggle_status(state: &AppState, mock: &MockApi) -> Option<u16> {
+    let toggle = mock.toggle_ref.as_ref()?;
+    let on = state.toggles.lock().unwrap().get(&toggle.name).copied().unwrap_or(false);
+    Some(if on { toggle.on_status } else { toggle.off_status })
+}
+
+fn check_request_schema(schema: &str, headers: &HeaderMap, body: &str) -> Vec<String> {
+    let is_json = headers.get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .is_some_and(|v| v == "application/json" || v.ends_with("+json"));
+    if !is_json {
+        return Vec::new();
+    }
+    let schema = match serde_json::from_str::<serde_json::Value>(schema) {
+        Ok(schema) => schema,
+        Err(e) => return vec![format!("invalid request_schema: {}", e)],
+    };
+    match (jsonschema::validator_for(&schema), serde_json::from_str::<serde_json::Value>(body)) {
+        (Err(e), _) => vec![format!("invalid request_schema: {}", e)],
+        (_, Err(e)) => vec![format!("body is not valid JSON: {}", e)],
+        (Ok(validator), Ok(instance)) => validator.iter_errors(&instance)
+            .map(|error| format!("body {}: {}", error.instance_path, error))
+            .collect(),
+    }
+}
+
+// Weighted random pick; entries with a zero, negative or non-finite weight are never chosen.
+// Returns None when there is nothing to choose from.
+fn pick_weighted(responses: &[WeightedResponse], rng: &Mutex<StdRng>) -> Option<usize> {
+    let weight = |r: &WeightedResponse| if r.weight.is_finite() && r.weight > 0.0 { r.weight as f64 } else { 0.0 };
+    let total: f64 = responses.iter().map(weight).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut roll = rng.lock().unwrap().random::<f64>() * total;
+    for (i, r) in responses.iter().enumerate() {
+        let w = weight(r);
+        if w > 0.0 && roll < w {
+            return Some(i);
+        }
+        roll -= w;
+    }
+    // Floating point leftovers: fall back to the last eligible entry
+    responses.iter().rposition(|r| weight(r) > 0.0)
+}
+
+// Match a regex mock's pattern against the full request path. Captures are keyed by
+// group number ("0" is the whole match, "1" the first group, ...) and by name.
+fn match_regex_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    regex_captures(&compile_path_regex(pattern)?, path)
+}
+
+// Regex mock paths must match the whole request path
+fn compile_path_regex(pattern: &str) -> Option<regex::Regex> {
+    regex::Regex::new(&format!("^(?:{})$", pattern)).ok()
+}
+
+fn regex_captures(re: &regex::Regex, path: &str) -> Option<HashMap<String, String>> {
+    let caps = re.captures(path)?;
+
+    let mut params = HashMap::new();
+    for (i, group) in caps.iter().enumerate() {
+        if let Some(m) = group {
+            params.insert(i.to_string(), m.as_str().to_string());
+        }
+    }
+    for name in re.capture_names().flatten() {
+        if let Some(m) = caps.name(name) {
+            params.insert(name.to_string(), m.as_str().to_string());
+        }
+    }
+    Some(params)
+}
+
+// Match a param mock such as "/users/:id/posts/:post" segment by segment, capturing
+// each `:name` segment under its name
+fn match_param_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let pattern_segments: Vec<&str> = pattern.split('/').collect();
+    let path_segments: Vec<&str> = path.split('/').collect();
+    if pattern_segments.len() != path_segments.len() {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    for (p, s) in pattern_segments.iter().zip(&path_segments) {
+        match p.strip_prefix(':') {
+            Some(name) if !s.is_empty() => {
+                params.insert(name.to_string(), s.to_string());
+            }
+            Some(_) => return None,
+            None if p == s => {}
+            None => return None,
+        }
+    }
+    Some(params)
+}
+
+// Whether a mock would serve requests for a route template such as "/users/{id}".
+// Template params match any `:name`/`{name}` segment of an exact mock, and are tried
+// against regex and wildcard mocks as a sample value.
+pub fn mock_covers_route(mock: &MockApi, method: &str, template: &str) -> bool {
+    if mock.method != "ANY" && !mock.method.eq_ignore_ascii_case(method) {
+        return false;
+    }
+    let is_param = |segment: &str| segment.starts_with(':') || (segment.starts_with('{') && segment.ends_with('}'));
+    let sample: String = template
+        .split('/')
+        .map(|segment| if is_param(segment) { "1" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if mock.match_type == "regex" {
+        return match_regex_path(&mock.path, &sample).is_some();
+    }
+    if let Some(prefix) = mock.path.strip_suffix('*') {
+        if mock.response_type == "proxy" || mock.response_type == "static" {
+            return sample.starts_with(prefix);
+        }
+    }
+
+    let mock_segments: Vec<&str> = mock.path.split('/').collect();
+    let route_segments: Vec<&str> = template.split('/').collect();
+    mock_segments.len() == route_segments.len()
+        && mock_segments
+            .iter()
+            .zip(&route_segments)
+            .all(|(m, r)| m == r || is_param(m) || is_param(r))
+}
+
+// Substitute `$1`, `${1}`, `$name` and `${name}` with captured params (`$$` is a literal `$`).
+// Like the regex crate, `$name` takes the longest run of word characters and unknown
+// names expand to nothing.
+fn expand_params(template: &str, params: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(stripped) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = stripped;
+        } else if let Some(end) = rest.strip_prefix('{').and_then(|r| r.find('}')) {
+            let name = &rest[1..end + 1];
+            out.push_str(params.get(name).map(String::as_str).unwrap_or(""));
+            rest = &rest[end + 2..];
+        } else {
+            let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            if end == 0 {
+                out.push('$');
+            } else {
+                out.push_str(params.get(&rest[..end]).map(String::as_str).unwrap_or(""));
+            }
+            rest = &rest[end..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+const STATIC_INDEX_FILE: &str = "index.html";
+
+pub fn content_type_for_path(path: &std::path::Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match ext.as_str() {
+        "json" => "application/json",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "wasm" => "application/wasm",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp4" => "video/mp4",
+        "mp3" => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+// Absolute paths are used as-is; relative ones are resolved against the app data directory
+fn resolve_data_path(state: &AppState, path: &str) -> std::path::PathBuf {
+    use tauri::Manager;
+
+    let path = std::path::PathBuf::from(path.trim());
+    if path.is_absolute() {
+        return path;
+    }
+    let app_handle = state.app_handle.lock().unwrap().clone();
+    match app_handle.and_then(|handle| handle.path().app_data_dir().ok()) {
+        Some(dir) => dir.join(path),
+        None => path,
+    }
+}
+
+// Resolve `suffix` (the part of the request path after the mock prefix) inside `root`.
+// Only plain path segments are accepted so `..` can't escape the root, and the
+// canonicalized result is checked again to catch symlinks pointing outside it.
+fn resolve_static_path(root: &str, suffix: &str) -> Option<std::path::PathBuf> {
+    use std::path::{Component, Path};
+
+    let root = Path::new(root).canonicalize().ok()?;
+    let mut target = root.clone();
+    for component in Path::new(suffix.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(segment) => target.push(segment),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if target.is_dir() {
+        target.push(STATIC_INDEX_FILE);
+    }
+
+    let target = target.canonicalize().ok()?;
+    if target.starts_with(&root) && target.is_file() {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+async fn serve_static(root: &str, suffix: &str, request_headers: &HeaderMap) -> Response {
+    let Some(file_path) = resolve_static_path(root, suffix) else {
+        return (StatusCode::NOT_FOUND, format!("Not Found: {}", suffix)).into_response();
+    };
+    serve_file(&file_path, request_headers).await
+}
+
+// Stream a file from disk, honouring Range requests with 206 (one range), a
+// multipart/byteranges body (several ranges) or 416 when nothing is satisfiable
+async fn serve_file(file_path: &std::path::Path, request_headers: &HeaderMap) -> Response {
+    use axum::http::header;
+    use futures_util::{stream, StreamExt};
+
+    let len = match tokio::fs::metadata(file_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("File not found: {}", file_path.display())).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read {}: {}", file_path.display(), e)).into_response(),
+    };
+    let content_type = content_type_for_path(file_path);
+    let ranges = request_headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_byte_ranges(v, len));
+
+    let mut response = match ranges.as_deref() {
+        None => (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CONTENT_LENGTH, len.to_string()),
+            ],
+            Body::from_stream(file_slice_stream(file_path.to_path_buf(), 0, len).await),
+        ).into_response(),
+        Some([]) => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", len))],
+        ).into_response(),
+        Some(&[(start, end)]) => (
+            StatusCode::PARTIAL_CONTENT,
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len)),
+                (header::CONTENT_LENGTH, (end - start + 1).to_string()),
+            ],
+            Body::from_stream(file_slice_stream(file_path.to_path_buf(), start, end - start + 1).await),
+        ).into_response(),
+        Some(ranges) => {
+            let boundary = uuid::Uuid::new_v4().simple().to_string();
+            let multipart_type = format!("multipart/byteranges; boundary={}", boundary);
+            let path = file_path.to_path_buf();
+            let part_boundary = boundary.clone();
+            let parts = stream::iter(ranges.to_vec())
+                .then(move |(start, end)| {
+                    let head = format!(
+                        "\r\n--{}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                        part_boundary, content_type, start, end, len
+                    );
+                    let path = path.clone();
+                    async move {
+                        stream::once(async move { Ok(axum::body::Bytes::from(head)) })
+                            .chain(file_slice_stream(path, start, end - start + 1).await)
+                    }
+                })
+                .flatten()
+                .chain(stream::once(async move { Ok(axum::body::Bytes::from(format!("\r\n--{}--\r\n", boundary))) }));
+            (
+                StatusCode::PARTIAL_CONTENT,
+                [(header::CONTENT_TYPE, multipart_type)],
+                Body::from_stream(parts),
+            ).into_response()
+        }
+    };
+    response.headers_mut().insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    // Files can be larger than memory, so the logger must not buffer them
+    response.extensions_mut().insert(StreamedBody);
+    response
+}
+
+async fn file_slice_stream(path: std::path::PathBuf, start: u64, len: u64) -> futures_util::stream::BoxStream<'static, std::io::Result<axum::body::Bytes>> {
+    use futures_util::{stream, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(e) => return stream::once(async { Err(e) }).boxed(),
+    };
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+        return stream::once(async { Err(e) }).boxed();
+    }
+    tokio_util::io::ReaderStream::new(file.take(len)).boxed()
+}
+
+// Parse a "bytes=" Range header into inclusive (start, end) pairs for a file of `len`
+// bytes. None means the header should be ignored (malformed or another unit); an empty
+// list means no range is satisfiable.
+fn parse_byte_ranges(header: &str, len: u64) -> Option<Vec<(u64, u64)>> {
+    let specs = header.trim().strip_prefix("bytes=")?;
+    let mut ranges = Vec::new();
+    for spec in specs.split(',') {
+        let (start, end) = spec.trim().split_once('-')?;
+        match (start.trim(), end.trim()) {
+            ("", suffix) => {
+                let n: u64 = suffix.parse().ok()?;
+                if n > 0 && len > 0 {
+                    ranges.push((len - n.min(len), len - 1));
+                }
+            }
+            (start, "") => {
+                let start: u64 = start.parse().ok()?;
+                if start < len {
+                    ranges.push((start, len - 1));
+                }
+            }
+            (start, end) => {
+                let (start, end): (u64, u64) = (start.parse().ok()?, end.parse().ok()?);
+                if start > end {
+                    return None;
+                }
+                if start < len {
+                    ranges.push((start, end.min(len - 1)));
+                }
+            }
+        }
+    }
+    Some(ranges)
+}
+
+// Dial the upstream before accepting the upgrade so an unreachable target is reported
+// to the client as a 502 instead of an immediately closed socket.
+async fn proxy_websocket(ws: WebSocketUpgrade, target_url: String, path: String) -> Response {
+    let upstream = match tokio_tungstenite::connect_async(&target_url).await {
+        Ok((upstream, _)) => upstream,
+        Err(e) => return (StatusCode::BAD_GATEWAY, format!("WebSocket Proxy Error: {}", e)).into_response(),
+    };
+    println!("[WS PROXY] {} => {}", path, target_url);
+
+    ws.on_upgrade(move |client| async move {
+        let (to_upstream, to_client) = pump_websocket(client, upstream).await;
+        println!(
+            "[WS PROXY] {} closed ({} frames to upstream, {} frames to client)",
+            path, to_upstream, to_client
+        );
+    })
+}
+
+// Forward frames in both directions until either side closes or errors, then tear down
+// both. Returns the number of frames forwarded each way.
+async fn pump_websocket(
+    client: WebSocket,
+    upstream: tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+) -> (u64, u64) {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::{self, protocol::CloseFrame};
+
+    let (mut client_tx, mut client_rx) = client.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+    let mut to_upstream = 0u64;
+    let mut to_client = 0u64;
+
+    tokio::select! {
+        _ = async {
+            while let Some(Ok(msg)) = client_rx.next().await {
+                let is_close = matches!(msg, WsMessage::Close(_));
+                let msg = match msg {
+                    WsMessage::Text(t) => tungstenite::Message::Text(t),
+                    WsMessage::Binary(b) => tungstenite::Message::Binary(b),
+                    WsMessage::Ping(p) => tungstenite::Message::Ping(p),
+                    WsMessage::Pong(p) => tungstenite::Message::Pong(p),
+                    WsMessage::Close(frame) => tungstenite::Message::Close(frame.map(|f| CloseFrame {
+                        code: f.code.into(),
+                        reason: f.reason,
+                    })),
+                };
+                if upstream_tx.send(msg).await.is_err() || is_close {
+                    break;
+                }
+                to_upstream += 1;
+            }
+        } => {},
+        _ = async {
+            while let Some(Ok(msg)) = upstream_rx.next().await {
+                let is_close = matches!(msg, tungstenite::Message::Close(_));
+                let msg = match msg {
+                    tungstenite::Message::Text(t) => WsMessage::Text(t),
+                    tungstenite::Message::Binary(b) => WsMessage::Binary(b),
+                    tungstenite::Message::Ping(p) => WsMessage::Ping(p),
+                    tungstenite::Message::Pong(p) => WsMessage::Pong(p),
+                    tungstenite::Message::Close(frame) => WsMessage::Close(frame.map(|f| axum::extract::ws::CloseFrame {
+                        code: f.code.into(),
+                        reason: f.reason,
+                    })),
+                    // Raw frames are never yielded when reading
+                    tungstenite::Message::Frame(_) => continue,
+                };
+                if client_tx.send(msg).await.is_err() || is_close {
+                    break;
+                }
+                to_client += 1;
+            }
+        } => {},
+    }
+
+    let _ = client_tx.close().await;
+    let _ = upstream_tx.close().await;
+    (to_upstream, to_client)
+}
+
+// Serve a WebSocket without an upstream: echo text and binary frames back, or replay
+// `script` (a JSON array of WsScriptMessage) and then hold the socket open until the
+// client closes it. Messages from the client are ignored while replaying, but a close
+// ends the replay, even in the middle of a delay.
+fn websocket_mock(ws: WebSocketUpgrade, script: &str, path: String) -> Response {
+    let script = if script.trim().is_empty() {
+        None
+    } else {
+        match serde_json::from_str::<Vec<WsScriptMessage>>(script) {
+            Ok(messages) => Some(messages),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid websocket script: {}", e)).into_response(),
+        }
+    };
+    println!("[WS MOCK] {} ({})", path, if script.is_some() { "replay" } else { "echo" });
+
+    ws.on_upgrade(move |mut socket| async move {
+        let mut sent = 0u64;
+        match script {
+            None => {
+                while let Some(Ok(msg)) = socket.recv().await {
+                    match msg {
+                        WsMessage::Text(_) | WsMessage::Binary(_) => {
+                            if socket.send(msg).await.is_err() {
+                                break;
+                            }
+                            sent += 1;
+                        }
+                        WsMessage::Close(_) => break,
+                        // Pings are answered by the socket itself
+                        _ => {}
+                    }
+                }
+            }
+            Some(messages) => {
+                'replay: for step in messages {
+                    if step.delay_ms > 0 {
+                        let delay = tokio::time::sleep(std::time::Duration::from_millis(step.delay_ms));
+                        tokio::pin!(delay);
+                        loop {
+                            tokio::select! {
+                                _ = &mut delay => break,
+                                msg = socket.recv() => match msg {
+                                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break 'replay,
+                                    Some(Ok(_)) => {}
+                                },
+                            }
+                        }
+                    }
+                    let text = match step.message {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    if socket.send(WsMessage::Text(text)).await.is_err() {
+                        break;
+                    }
+                    sent += 1;
+                }
+                while let Some(Ok(msg)) = socket.recv().await {
+                    if matches!(msg, WsMessage::Close(_)) {
+                        break;
+                    }
+                }
+            }
+        }
+        println!("[WS MOCK] {} closed ({} messages sent)", path, sent);
+    })
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    pub method: Option<String>,
+    // SQL LIKE pattern, e.g. "/api/%"
+    pub path_like: Option<String>,
+    pub status_min: Option<u16>,
+    pub status_max: Option<u16>,
+    // Epoch millis, inclusive
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+impl LogFilter {
+    // In-memory equivalent of the SQL filter, used when the SQLite store is disabled
+    pub fn matches(&self, log: &RequestLog) -> bool {
+        if let Some(method) = &self.method {
+            if !method.eq_ignore_ascii_case(&log.method) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.path_like {
+            // LIKE: % is any run, _ is one character, ASCII case-insensitive
+            let re: String = pattern.chars().map(|c| match c {
+                '%' => ".*".to_string(),
+                '_' => ".".to_string(),
+                c => regex::escape(&c.to_string()),
+            }).collect();
+            match regex::Regex::new(&format!("(?i)^{}$", re)) {
+                Ok(re) if re.is_match(&log.path) => {}
+                _ => return false,
+            }
+        }
+        self.status_min.is_none_or(|min| log.status_code >= min)
+            && self.status_max.is_none_or(|max| log.status_code <= max)
+            && self.since.is_none_or(|since| log.timestamp >= since)
+            && self.until.is_none_or(|until| log.timestamp <= until)
+    }
+}
+
+// Filter columns are stored alongside the full log as JSON, so fields added to
+// RequestLog later round-trip without a schema change.
+pub async fn init_log_store(pool: &sqlx::SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS request_logs (
+            id TEXT PRIMARY KEY,
+            method TEXT NOT NULL,
+            path TEXT NOT NULL,
+            status_code INTEGER NOT NULL,
+            timestamp INTEGER NOT NULL,
+            data TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS request_logs_timestamp ON request_logs (timestamp)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn insert_log_record(pool: &sqlx::SqlitePool, log: &RequestLog) -> Result<(), sqlx::Error> {
+    let data = serde_json::to_string(log).unwrap_or_default();
+    sqlx::query("INSERT OR REPLACE INTO request_logs (id, method, path, status_code, timestamp, data) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(&log.id)
+        .bind(&log.method)
+        .bind(&log.path)
+        .bind(log.status_code as i64)
+        .bind(log.timestamp as i64)
+        .bind(data)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn query_log_store(pool: &sqlx::SqlitePool, filter: &LogFilter) -> Result<Vec<RequestLog>, sqlx::Error> {
+    use sqlx::Row;
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT data FROM request_logs WHERE 1 = 1");
+    if let Some(method) = &filter.method {
+        query.push(" AND method = ").push_bind(method.to_uppercase());
+    }
+    if let Some(pattern) = &filter.path_like {
+        query.push(" AND path LIKE ").push_bind(pattern.clone());
+    }
+    if let Some(min) = filter.status_min {
+        query.push(" AND status_code >= ").push_bind(min as i64);
+    }
+    if let Some(max) = filter.status_max {
+        query.push(" AND status_code <= ").push_bind(max as i64);
+    }
+    if let Some(since) = filter.since {
+        query.push(" AND timestamp >= ").push_bind(since as i64);
+    }
+    if let Some(until) = filter.until {
+        query.push(" AND timestamp <= ").push_bind(until as i64);
+    }
+    query.push(" ORDER BY timestamp DESC LIMIT ").push_bind(filter.limit.unwrap_or(1000) as i64);
+
+    let rows = query.build().fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| serde_json::from_str(row.get::<&str, _>("data")).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state(mocks: Vec<MockApi>) -> AppState {
+        AppState {
+            mocks: Arc::new(Mutex::new(mocks.into_iter().map(|mock| (mock.id.clone(), mock)).collect())),
+            db_connections: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(Mutex::new(ServerConfig::default())),
+            logs: Arc::new(Mutex::new(VecDeque::new())),
+            app_handle: Arc::new(Mutex::new(None)),
+            request_count: Arc::new(Mutex::new(0)),
+            metrics: Arc::new(Mutex::new(Default::default())),
+            log_db: Arc::new(Mutex::new(None)),
+            mock_counters: Arc::new(Mutex::new(HashMap::new())),
+            started_at: Arc::new(Mutex::new(None)),
+            rng: Arc::new(Mutex::new(rand::SeedableRng::from_os_rng())),
+            environments: Arc::new(Mutex::new(HashMap::new())),
+            config_load_errors: Arc::new(Mutex::new(HashMap::new())),
+            proxy_client: Arc::new(Mutex::new(reqwest::Client::new())),
+            toggles: Arc::new(Mutex::new(HashMap::new())),
+            fixtures: Arc::new(Mutex::new(HashMap::new())),
+            proxy_rotation: Arc::new(Mutex::new(HashMap::new())),
+            regex_cache: Arc::new(Mutex::new(HashMap::new())),
+            sequence_positions: Arc::new(Mutex::new(HashMap::new())),
+            kv: Arc::new(Mutex::new(HashMap::new())),
+            running_flag: Arc::new(Mutex::new(false)),
+            server_task: Arc::new(Mutex::new(None)),
+            db_status: Arc::new(Mutex::new(HashMap::new())),
+            servers: Arc::new(Mutex::new(HashMap::new())),
+            serving_port: 0,
+        }
+    }
+
+    fn mock(id: &str, method: &str, path: &str, match_type: &str) -> MockApi {
+        MockApi {
+            id: id.to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+            match_type: match_type.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn explain(state: &AppState, method: &str, path: &str) -> MatchExplanation {
+        let headers = HeaderMap::new();
+        explain_match(state, &MatchRequest::new(state, method, path, None, &headers, ""))
+    }
+
+    #[test]
+    fn exact_beats_param_beats_regex_beats_wildcard() {
+        let mut mocks = vec![
+            mock("exact", "GET", "/users/1", "exact"),
+            mock("param", "GET", "/users/:id", "param"),
+            mock("regex", "GET", r"/users/\d+", "regex"),
+            mock("wildcard", "GET", "/users/*", "exact"),
+        ];
+        for expected in ["exact", "param", "regex", "wildcard"] {
+            let result = explain(&test_state(mocks.clone()), "GET", "/users/1");
+            assert_eq!(result.rule, expected);
+            assert_eq!(result.mock_id.as_deref(), Some(expected));
+            mocks.remove(0);
+        }
+    }
+
+    #[test]
+    fn priority_outranks_the_path_kind() {
+        let mut wildcard = mock("wildcard", "GET", "/users/*", "exact");
+        wildcard.priority = 1;
+        let state = test_state(vec![mock("exact", "GET", "/users/1", "exact"), wildcard]);
+        let result = explain(&state, "GET", "/users/1");
+        assert_eq!(result.mock_id.as_deref(), Some("wildcard"));
+        assert_eq!(result.params.get("wildcard").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn mocks_of_other_groups_are_skipped() {
+        let mut grouped = mock("grouped", "GET", "/users", "exact");
+        grouped.group = Some("beta".to_string());
+        grouped.priority = 1;
+        let state = test_state(vec![grouped, mock("ungrouped", "GET", "/users", "exact")]);
+
+        state.config.lock().unwrap().active_group = Some("alpha".to_string());
+        assert_eq!(explain(&state, "GET", "/users").mock_id.as_deref(), Some("ungrouped"));
+
+        state.config.lock().unwrap().active_group = Some("beta".to_string());
+        assert_eq!(explain(&state, "GET", "/users").mock_id.as_deref(), Some("grouped"));
+    }
+
+    #[test]
+    fn port_bound_mocks_only_match_their_instance() {
+        let mut bound = mock("bound", "GET", "/users", "exact");
+        bound.port = Some(9000);
+        let mut state = test_state(vec![bound]);
+
+        state.serving_port = 8000;
+        let result = explain(&state, "GET", "/users");
+        assert_eq!(result.rule, "not-found");
+        assert_eq!(result.near_misses[0].reason, "bound to port 9000");
+
+        state.serving_port = 9000;
+        assert_eq!(explain(&state, "GET", "/users").mock_id.as_deref(), Some("bound"));
+    }
+
+    #[test]
+    fn other_methods_on_a_mocked_path_are_405() {
+        let state = test_state(vec![
+            mock("post", "POST", "/users/:id", "param"),
+            mock("put", "PUT", "/users/1", "exact"),
+        ]);
+        let result = explain(&state, "GET", "/users/1");
+        assert_eq!(result.rule, "method-not-allowed");
+        assert_eq!(method_not_allowed(&state, &MatchRequest::new(&state, "GET", "/users/1", None, &HeaderMap::new(), "")), Some(vec!["POST".to_string(), "PUT".to_string()]));
+    }
+
+    #[test]
+    fn skipped_mocks_dont_make_a_405() {
+        let mut other_host = mock("post", "POST", "/users", "exact");
+        other_host.host_match = Some("api.example.com".to_string());
+        let state = test_state(vec![other_host]);
+        assert_eq!(explain(&state, "GET", "/users").rule, "not-found");
+    }
+
+    #[test]
+    fn used_up_quota_is_reported() {
+        let state = test_state(vec![mock("users", "GET", "/users", "exact")]);
+        state.config.lock().unwrap().global_quota = Some(GlobalQuota { limit: 2, status: 429, body: String::new(), retry_after_secs: 60 });
+
+        *state.request_count.lock().unwrap() = 1;
+        assert_eq!(explain(&state, "GET", "/users").rule, "exact");
+
+        *state.request_count.lock().unwrap() = 2;
+        assert_eq!(explain(&state, "GET", "/users").rule, "quota");
+    }
+
+    fn render(text: &str) -> String {
+        let headers = HeaderMap::new();
+        let rng = Mutex::new(<StdRng as rand::SeedableRng>::seed_from_u64(1));
+        render_template(text, &TemplateContext {
+            method: "GET",
+            path: "/users",
+            query: &[],
+            headers: &headers,
+            params: &HashMap::new(),
+            now_ms: 1_700_000_000_123,
+            rng: &rng,
+        })
+    }
+
+    #[test]
+    fn templates_expand_known_tokens() {
+        assert_eq!(render("{{ request.method }} {{request.path}} at {{now}}"), "GET /users at 1700000000123");
+        assert_eq!(render("{{randomInt 5 5}}"), "5");
+    }
+
+    #[test]
+    fn templates_leave_unknown_and_invalid_tokens() {
+        assert_eq!(render("{{nope}} and {{request.query.missing}}"), "{{nope}} and {{request.query.missing}}");
+        assert_eq!(render("{{randomInt 9 1}}"), "{{randomInt 9 1}}");
+        assert_eq!(render("a {{uuid"), "a {{uuid");
+    }
+
+    #[test]
+    fn byte_ranges() {
+        assert_eq!(parse_byte_ranges("bytes=-4", 10), Some(vec![(6, 9)]));
+        assert_eq!(parse_byte_ranges("bytes=-40", 10), Some(vec![(0, 9)]));
+        assert_eq!(parse_byte_ranges("bytes=20-30", 10), Some(vec![]));
+        assert_eq!(parse_byte_ranges("bytes=5-30", 10), Some(vec![(5, 9)]));
+        assert_eq!(parse_byte_ranges("bytes=0-1, 4-", 10), Some(vec![(0, 1), (4, 9)]));
+        assert_eq!(parse_byte_ranges("bytes=3-1", 10), None);
+        assert_eq!(parse_byte_ranges("items=0-1", 10), None);
+    }
+
+    #[test]
+    fn params_expand_into_templates() {
+        let params = HashMap::from([("1".to_string(), "42".to_string()), ("name".to_string(), "ada".to_string())]);
+        assert_eq!(expand_params("/u/$1/${name}/$name.json", &params), "/u/42/ada/ada.json");
+        assert_eq!(expand_params("$$1 $missing $", &params), "$1  $");
+    }
+
+    #[test]
+    fn json_containment() {
+        let actual = serde_json::json!({ "user": { "id": 1, "tags": ["a", "b"] }, "extra": true });
+        assert!(json_contains(&actual, &serde_json::json!({ "user": { "id": 1 } })));
+        assert!(json_contains(&actual, &serde_json::json!({ "user": { "tags": ["b"] } })));
+        assert!(!json_contains(&actual, &serde_json::json!({ "user": { "id": 2 } })));
+        assert!(!json_contains(&actual, &serde_json::json!({ "missing": null })));
+    }
+
+    #[test]
+    fn zero_weights_pick_nothing() {
+        let rng = Mutex::new(<StdRng as rand::SeedableRng>::seed_from_u64(1));
+        let responses = vec![
+            WeightedResponse { weight: 0.0, status: 200, body: String::new() },
+            WeightedResponse { weight: -1.0, status: 500, body: String::new() },
+        ];
+        assert_eq!(pick_weighted(&responses, &rng), None);
+        assert_eq!(pick_weighted(&[], &rng), None);
+    }
+
+    #[test]
+    fn histogram_bucket_bounds_are_inclusive() {
+        let mut histogram = LatencyHistogram::default();
+        for value in [10.0, 10.5, 50.0, 51.0] {
+            histogram.observe(&[50.0, 10.0], value);
+        }
+        assert_eq!(histogram.bounds, vec![10.0, 50.0]);
+        assert_eq!(histogram.counts, vec![1, 2]);
+        assert_eq!(histogram.count, 4);
+        assert!(histogram.render().contains("_bucket{le=\"50\"} 3\n"));
+    }
+
+    #[test]
+    fn static_paths_stay_inside_the_root() {
+        let root = std::env::temp_dir().join(format!("evo-static-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::write(root.join("assets/app.js"), "").unwrap();
+        std::fs::write(root.join("index.html"), "").unwrap();
+        let root_str = root.to_str().unwrap();
+
+        assert!(resolve_static_path(root_str, "/assets/app.js").is_some_and(|p| p.ends_with("assets/app.js")));
+        assert!(resolve_static_path(root_str, "/").is_some_and(|p| p.ends_with("index.html")));
+        assert_eq!(resolve_static_path(root_str, "/assets/../../etc/passwd"), None);
+        assert_eq!(resolve_static_path(root_str, "/../index.html"), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
 }